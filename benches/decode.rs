@@ -0,0 +1,16 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use optitrack::Message;
+
+const SAMPLE_FRAME_DATA: &[u8] = include_bytes!("../src/FrameData.bin");
+
+fn decode_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(SAMPLE_FRAME_DATA.len() as u64));
+    group.bench_function("frame_data", |b| {
+        b.iter(|| Message::from_bytes(black_box(SAMPLE_FRAME_DATA)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode_frame);
+criterion_main!(benches);
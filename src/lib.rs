@@ -1,8 +1,19 @@
-use bytes::{Buf, BufMut, BytesMut};
-use glam::{Quat, Vec3};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use glam::{DVec3, Quat, Vec3};
+use serde::{Deserialize, Serialize};
 use std::{
-    error,
-    io::{self, BufRead},
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    error, fmt,
+    io::{self, BufRead, Read},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, OnceLock,
+    },
+    thread::JoinHandle,
+    time::Duration,
 };
 
 pub const VERSION: [u8; 4] = [4, 1, 0, 0];
@@ -22,7 +33,7 @@ pub const fn connect_packet() -> [u8; 270] {
 
 pub const fn keepalive_packet() -> [u8; 12] {
     let mut packet = [0u8; 12];
-    let command = &(MessageId::KeepAlive as u16).to_le_bytes();
+    let command = &MessageId::KeepAlive.to_u16().to_le_bytes();
     packet[0] = command[0];
     packet[1] = command[1];
     packet[2] = b'k';
@@ -39,12 +50,15 @@ pub const fn keepalive_packet() -> [u8; 12] {
 
 pub const fn request_modeldef_packet() -> [u8; 5] {
     let mut payload = [0u8; 5];
-    let command: [u8; 2] = (MessageId::RequestModelDef as u16).to_le_bytes();
+    let command: [u8; 2] = MessageId::RequestModelDef.to_u16().to_le_bytes();
     payload[0] = command[0];
     payload[1] = command[1];
     payload
 }
 
+// Every codec, message, and client type is `Send + Sync` (checked by the `send_sync` test).
+// Codecs only hold configuration, so a configured codec can be copied or shared behind a lock by
+// worker threads decoding different datagrams.
 pub trait Encoder<Item> {
     type Error: From<io::Error>;
     fn encode(&mut self, item: Item, dst: &mut BytesMut) -> Result<(), Self::Error>;
@@ -56,15 +70,220 @@ pub trait Decoder {
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error>;
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    NotEnoughBytes {
+        item: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    NonFinite {
+        field: &'static str,
+    },
+    // a section's elements didn't fill exactly the byte count its header declared
+    SectionSizeMismatch {
+        section: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    // a section's element count and byte count can't both be right, e.g. elements in an empty
+    // section
+    InconsistentSection {
+        section: &'static str,
+    },
+    // no data arrived within the client read timeout
+    Timeout,
+    Io {
+        kind: io::ErrorKind,
+    },
+    // any other decode failure, flattened to its message
+    Invalid {
+        reason: String,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NotEnoughBytes {
+                item,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Not enough bytes to decode {}.  Expected: {}, Got: {}",
+                item, expected, got
+            ),
+            DecodeError::NonFinite { field } => {
+                write!(f, "Non-finite value decoded for {}", field)
+            }
+            DecodeError::SectionSizeMismatch {
+                section,
+                expected,
+                got,
+            } => write!(
+                f,
+                "{} declared {} bytes but its elements used {}",
+                section, expected, got
+            ),
+            DecodeError::InconsistentSection { section } => {
+                write!(f, "Inconsistent {} section header", section)
+            }
+            DecodeError::Timeout => write!(f, "Timed out waiting for data"),
+            DecodeError::Io { kind } => write!(f, "I/O error while decoding: {}", kind),
+            DecodeError::Invalid { reason } => write!(f, "Invalid message: {}", reason),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl From<io::Error> for DecodeError {
+    fn from(e: io::Error) -> Self {
+        DecodeError::Io { kind: e.kind() }
+    }
+}
+
+impl From<Box<dyn error::Error>> for DecodeError {
+    fn from(e: Box<dyn error::Error>) -> Self {
+        match e.downcast::<DecodeError>() {
+            Ok(e) => *e,
+            Err(e) => DecodeError::Invalid {
+                reason: e.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    // the named message variant has no wire encoding, so nothing was written
+    Unsupported(&'static str),
+    // the named value is too large for the field the format stores it in
+    Overflow(&'static str),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Unsupported(variant) => write!(f, "Encoding {} is not supported", variant),
+            EncodeError::Overflow(field) => write!(f, "{} overflows its encoded field", field),
+        }
+    }
+}
+
+impl error::Error for EncodeError {}
+
+fn ensure_finite(field: &'static str, is_finite: bool) -> Result<(), DecodeError> {
+    if is_finite {
+        Ok(())
+    } else {
+        Err(DecodeError::NonFinite { field })
+    }
+}
+
+// A zero or non-finite quaternion has no direction to normalize to (and glam asserts on it), so
+// strict decoding rejects it and lenient decoding keeps it as sent
+fn normalize_rot(field: &'static str, rot: Quat, strict: bool) -> Result<Quat, DecodeError> {
+    if rot.is_finite() && rot.length_squared() > 0.0 {
+        Ok(rot.normalize())
+    } else if strict {
+        Err(DecodeError::NonFinite { field })
+    } else {
+        Ok(rot)
+    }
+}
+
+// How a message header is laid out ahead of its payload
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum HeaderLayout {
+    #[default]
+    IdAndSize,
+    // some embedded emitters omit the size field, so the rest of the buffer is the payload
+    IdOnly,
+}
+
 #[derive(Debug)]
 pub enum Message {
+    // a ping carries the client's sender block, which has the same layout as the server's reply
+    Ping(Box<PingResponse>),
     PingResponse(Box<PingResponse>),
+    Response(Box<Response>),
     FrameData(Box<FrameData>),
     ModelDef(Box<ModelDef>),
+    KeepAlive,
+    Disconnect,
+    DisconnectByTimeout,
     Unknown,
 }
 
 impl Message {
+    // The ping a client sends to start the handshake
+    pub fn ping() -> Self {
+        Message::Ping(Box::new(PingResponse {
+            packet_size: SERVER_INFO_LEN as i16,
+            app_name: "Ping".to_string(),
+            server_version: [0; 4],
+            natnet_version: VERSION,
+        }))
+    }
+
+    pub fn ping_response(server: PingResponse) -> Self {
+        Message::PingResponse(Box::new(server))
+    }
+
+    pub fn as_frame_data(&self) -> Option<&FrameData> {
+        match self {
+            Message::FrameData(frame_data) => Some(frame_data),
+            _ => None,
+        }
+    }
+
+    pub fn into_frame_data(self) -> Option<Box<FrameData>> {
+        match self {
+            Message::FrameData(frame_data) => Some(frame_data),
+            _ => None,
+        }
+    }
+
+    pub fn as_model_def(&self) -> Option<&ModelDef> {
+        match self {
+            Message::ModelDef(model_def) => Some(model_def),
+            _ => None,
+        }
+    }
+
+    pub fn into_model_def(self) -> Option<Box<ModelDef>> {
+        match self {
+            Message::ModelDef(model_def) => Some(model_def),
+            _ => None,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<BytesMut, Box<dyn error::Error>> {
+        match self {
+            Message::FrameData(frame_data) => frame_data.to_packet(),
+            Message::ModelDef(model_def) => model_def.to_packet(),
+            Message::KeepAlive => Ok(empty_message(MessageId::KeepAlive)),
+            Message::Disconnect => Ok(empty_message(MessageId::Disconnect)),
+            Message::DisconnectByTimeout => Ok(empty_message(MessageId::DisconnectByTimeout)),
+            Message::Ping(sender) => {
+                let mut dst = BytesMut::new();
+                dst.put_u16_le(MessageId::Ping.into());
+                PingResponseCodec.encode(sender.as_ref().clone(), &mut dst)?;
+                Ok(dst)
+            }
+            Message::PingResponse(server_info) => {
+                let mut dst = BytesMut::new();
+                dst.put_u16_le(MessageId::PingResponse.into());
+                PingResponseCodec.encode(server_info.as_ref().clone(), &mut dst)?;
+                Ok(dst)
+            }
+            Message::Response(_) => Err(EncodeError::Unsupported("Response").into()),
+            Message::Unknown => Err(EncodeError::Unsupported("Unknown").into()),
+        }
+    }
+
     pub fn peek_id(src: &[u8]) -> Option<MessageId> {
         if src.len() < size_of::<u16>() {
             log::warn!(
@@ -80,6 +299,38 @@ impl Message {
     }
 
     pub fn from_bytes(src: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut bytes = BytesMut::from(src);
+        Self::decode(&mut bytes)
+    }
+
+    pub fn from_bytes_with_layout(
+        src: &[u8],
+        layout: HeaderLayout,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        match layout {
+            HeaderLayout::IdAndSize => Self::from_bytes(src),
+            HeaderLayout::IdOnly => {
+                if src.len() < size_of::<u16>() {
+                    return Err(DecodeError::NotEnoughBytes {
+                        item: "Message ID",
+                        expected: size_of::<u16>(),
+                        got: src.len(),
+                    }
+                    .into());
+                }
+                // restore the size field the codecs expect ahead of the payload
+                let (id, payload) = src.split_at(size_of::<u16>());
+                let mut bytes = BytesMut::with_capacity(src.len() + size_of::<u16>());
+                bytes.extend_from_slice(id);
+                bytes.put_u16_le(u16::try_from(payload.len())?);
+                bytes.extend_from_slice(payload);
+                Self::decode(&mut bytes)
+            }
+        }
+    }
+
+    // Decodes a single message from the front of `src`, leaving any trailing bytes in place
+    pub fn decode(src: &mut BytesMut) -> Result<Self, Box<dyn std::error::Error>> {
         if src.len() < size_of::<u16>() {
             return Err(format!(
                 "Not enough bytes for message ID.  Expected: {}, Got: {}",
@@ -88,53 +339,399 @@ impl Message {
             )
             .into());
         }
-        let mut bytes = BytesMut::from(src);
-        let message_id = bytes.get_u16_le();
+        let message_id = src.get_u16_le();
         log::debug!("Message ID: {}", message_id);
         let message_id = match message_id.into() {
+            // a bare ping id (or one with an empty packet size) has no sender block
+            MessageId::Ping if src.remaining() <= size_of::<u16>() => {
+                skip_empty_payload(src);
+                Message::Ping(Box::new(PingResponse {
+                    packet_size: 0,
+                    app_name: String::new(),
+                    server_version: [0; 4],
+                    natnet_version: [0; 4],
+                }))
+            }
+            MessageId::Ping => {
+                let mut codec = PingResponseCodec;
+                let sender = codec.decode(src)?;
+                Message::Ping(Box::new(sender))
+            }
             MessageId::PingResponse => {
                 let mut codec = PingResponseCodec;
-                let ping_res = codec.decode(&mut bytes)?;
+                let ping_res = codec.decode(src)?;
                 Message::PingResponse(Box::new(ping_res))
             }
+            MessageId::Response => {
+                let mut codec = ResponseCodec;
+                let response = codec.decode(src)?;
+                Message::Response(Box::new(response))
+            }
             MessageId::FrameData => {
-                let mut codec = FrameDataCodec;
-                let frame_data = codec.decode(&mut bytes)?;
+                let mut codec = FrameDataCodec::default();
+                let frame_data = codec.decode(src)?;
                 Message::FrameData(Box::new(frame_data))
             }
             MessageId::ModelDef => {
-                let mut codec = ModelDefCodec;
-                let modeldef = codec.decode(&mut bytes)?;
+                let mut codec = ModelDefCodec::default();
+                let modeldef = codec.decode(src)?;
                 Message::ModelDef(Box::new(modeldef))
             }
+            MessageId::KeepAlive => {
+                skip_empty_payload(src);
+                Message::KeepAlive
+            }
+            MessageId::Disconnect => {
+                skip_empty_payload(src);
+                Message::Disconnect
+            }
+            MessageId::DisconnectByTimeout => {
+                skip_empty_payload(src);
+                Message::DisconnectByTimeout
+            }
             id => {
                 log::error!("Got message type: {:?}", id);
-                unimplemented!()
+                return Err(format!("Unsupported message type: {:?}", id).into());
             }
         };
         Ok(message_id)
     }
 }
 
+/* MessageFramer */
+
+// Size of the message id and packet size fields that prefix every message
+const HEADER_LEN: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct MessageFramer {
+    buf: BytesMut,
+}
+
+impl MessageFramer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn extend_from_slice(&mut self, src: &[u8]) {
+        self.buf.extend_from_slice(src);
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn peek_header(src: &[u8]) -> Option<(MessageId, usize)> {
+        if src.len() < HEADER_LEN {
+            return None;
+        }
+        let id = MessageId::from(u16::from_le_bytes([src[0], src[1]]));
+        let size = u16::from_le_bytes([src[2], src[3]]) as usize;
+        Some((id, size))
+    }
+
+    // Describes the partial message left in the buffer when its source ends
+    fn incomplete_message_error(&self) -> DecodeError {
+        DecodeError::NotEnoughBytes {
+            item: "Message",
+            expected: Self::peek_header(&self.buf)
+                .map(|(_, size)| HEADER_LEN + size)
+                .unwrap_or(HEADER_LEN),
+            got: self.buf.len(),
+        }
+    }
+
+    // Returns `None` until a whole message is buffered.  Bytes are only consumed on success, so
+    // after an error the caller should `resync` to skip past the bad message.
+    pub fn next_message(&mut self) -> Option<Result<Message, Box<dyn error::Error>>> {
+        let (id, size) = Self::peek_header(&self.buf)?;
+        if let MessageId::Unrecognized(id) = id {
+            return Some(Err(format!("Unrecognized message ID: {}", id).into()));
+        }
+        if self.buf.len() < HEADER_LEN + size {
+            return None;
+        }
+        // corrupt section counts would otherwise run the decoder past the end of the message
+        if matches!(id, MessageId::FrameData) {
+            let body = BytesMut::from(&self.buf[2..HEADER_LEN + size]);
+            if let Err(e) = FrameDataCodec::precheck(&body) {
+                return Some(Err(e.into()));
+            }
+        }
+        match Message::from_bytes(&self.buf[..HEADER_LEN + size]) {
+            Ok(message) => {
+                self.buf.advance(HEADER_LEN + size);
+                Some(Ok(message))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    // Appends `src` and visits the rigid bodies of every whole FrameData message buffered, reading
+    // them in place rather than decoding a `Message`.  Other messages are consumed and skipped.
+    // Returns the number of frames visited; on error nothing is consumed, as with `next_message`.
+    pub fn for_each_rigid_body<F: FnMut(&RigidBody)>(
+        &mut self,
+        src: &[u8],
+        mut f: F,
+    ) -> Result<usize, Box<dyn error::Error>> {
+        self.buf.extend_from_slice(src);
+        let codec = FrameDataCodec::default();
+        let mut frames = 0;
+        while let Some((id, size)) = Self::peek_header(&self.buf) {
+            if self.buf.len() < HEADER_LEN + size {
+                break;
+            }
+            match id {
+                MessageId::FrameData => {
+                    codec.for_each_rigid_body(&self.buf[2..HEADER_LEN + size], &mut f)?;
+                    frames += 1;
+                }
+                MessageId::Unrecognized(id) => {
+                    return Err(format!("Unrecognized message ID: {}", id).into());
+                }
+                id => log::debug!("Skipping {:?} while visiting rigid bodies", id),
+            }
+            self.buf.advance(HEADER_LEN + size);
+        }
+        Ok(frames)
+    }
+
+    // Discards bytes up to the next header with a known id whose message fits in the buffer,
+    // always skipping at least the current first byte.  Returns the number of bytes discarded.
+    pub fn resync(&mut self) -> usize {
+        let boundary = (1..self.buf.len())
+            .find(|&i| match Self::peek_header(&self.buf[i..]) {
+                Some((id, size)) => {
+                    !matches!(id, MessageId::Unrecognized(_))
+                        && i + HEADER_LEN + size <= self.buf.len()
+                }
+                None => false,
+            })
+            // keep a possible partial header at the end of the buffer
+            .unwrap_or_else(|| self.buf.len().saturating_sub(HEADER_LEN - 1).max(1));
+        let discarded = boundary.min(self.buf.len());
+        self.buf.advance(discarded);
+        log::warn!("Discarded {} bytes while resynchronizing", discarded);
+        discarded
+    }
+}
+
+/* MessageReader */
+
+// Decodes messages from any byte stream (e.g. a `UnixStream` relay or a file) using the same
+// framing as `MessageFramer`.  Iteration ends when the stream does.
+#[derive(Debug)]
+pub struct MessageReader<R> {
+    reader: R,
+    framer: MessageFramer,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            framer: MessageFramer::new(),
+            buf: vec![0u8; MAX_PACKET_SIZE],
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for MessageReader<R> {
+    type Item = Result<Message, Box<dyn error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.framer.next_message() {
+                Some(Ok(message)) => return Some(Ok(message)),
+                Some(Err(e)) => {
+                    self.framer.resync();
+                    return Some(Err(e));
+                }
+                None => {}
+            }
+            match self.reader.read(&mut self.buf) {
+                Ok(0) if self.framer.buffered() == 0 => return None,
+                Ok(0) => {
+                    let err = self.framer.incomplete_message_error();
+                    self.framer = MessageFramer::new();
+                    return Some(Err(err.into()));
+                }
+                Ok(len) => self.framer.extend_from_slice(&self.buf[..len]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+// Decodes every message in a capture of concatenated NatNet messages
+pub fn read_capture_file<P: AsRef<Path>>(path: P) -> Result<Vec<Message>, DecodeError> {
+    let mut framer = MessageFramer::new();
+    framer.extend_from_slice(&std::fs::read(path)?);
+    let mut messages = Vec::new();
+    while let Some(message) = framer.next_message() {
+        messages.push(message?);
+    }
+    if framer.buffered() > 0 {
+        return Err(framer.incomplete_message_error());
+    }
+    Ok(messages)
+}
+
+/* CaptureIndex */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CaptureEntry {
+    // offset of the message id within the capture
+    pub offset: usize,
+    pub id: MessageId,
+}
+
+// Message boundaries of a capture of concatenated NatNet messages, e.g. a memory-mapped file.
+// Only the headers are read while indexing; messages are decoded on demand with `decode_at`.
+#[derive(Debug)]
+pub struct CaptureIndex<'a> {
+    src: &'a [u8],
+    entries: Vec<CaptureEntry>,
+}
+
+impl<'a> CaptureIndex<'a> {
+    pub fn new(src: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset < src.len() {
+            let Some((id, size)) = MessageFramer::peek_header(&src[offset..]) else {
+                return Err(DecodeError::NotEnoughBytes {
+                    item: "Message header",
+                    expected: HEADER_LEN,
+                    got: src.len() - offset,
+                });
+            };
+            if let MessageId::Unrecognized(id) = id {
+                return Err(DecodeError::Invalid {
+                    reason: format!("Unrecognized message ID {} at offset {}", id, offset),
+                });
+            }
+            if src.len() - offset < HEADER_LEN + size {
+                return Err(DecodeError::NotEnoughBytes {
+                    item: "Message",
+                    expected: HEADER_LEN + size,
+                    got: src.len() - offset,
+                });
+            }
+            entries.push(CaptureEntry { offset, id });
+            offset += HEADER_LEN + size;
+        }
+        Ok(Self { src, entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[CaptureEntry] {
+        &self.entries
+    }
+
+    // The bytes of the message at `index`, header included
+    pub fn message_bytes(&self, index: usize) -> Option<&'a [u8]> {
+        let offset = self.entries.get(index)?.offset;
+        let end = self
+            .entries
+            .get(index + 1)
+            .map_or(self.src.len(), |entry| entry.offset);
+        Some(&self.src[offset..end])
+    }
+
+    // Returns `None` when `index` is out of range
+    pub fn decode_at(&self, index: usize) -> Option<Result<Message, Box<dyn error::Error>>> {
+        self.message_bytes(index).map(Message::from_bytes)
+    }
+}
+
+/* NatNetFrame */
+
+// Adapts the message framing to `tokio_util::codec`, e.g. `Framed::new(stream, NatNetFrame::new())`
+#[cfg(feature = "tokio")]
+#[derive(Debug, Default)]
+pub struct NatNetFrame {}
+
+#[cfg(feature = "tokio")]
+impl NatNetFrame {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_util::codec::Decoder for NatNetFrame {
+    type Item = Message;
+    type Error = DecodeError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some((id, size)) = MessageFramer::peek_header(src) else {
+            return Ok(None);
+        };
+        if let MessageId::Unrecognized(id) = id {
+            return Err(DecodeError::Invalid {
+                reason: format!("Unrecognized message ID: {}", id),
+            });
+        }
+        if src.len() < HEADER_LEN + size {
+            src.reserve(HEADER_LEN + size - src.len());
+            return Ok(None);
+        }
+        let mut packet = src.split_to(HEADER_LEN + size);
+        Ok(Some(Message::decode(&mut packet)?))
+    }
+}
+
+// The id and an empty packet size
+fn empty_message(id: MessageId) -> BytesMut {
+    let mut dst = BytesMut::with_capacity(HEADER_LEN);
+    dst.put_u16_le(id.into());
+    dst.put_u16_le(0);
+    dst
+}
+
+// Messages without a payload may still carry a packet size, and a body it covers (e.g. a keep alive
+// padded with its name) is skipped.  A size reaching past the buffer is clamped to it.
+fn skip_empty_payload(src: &mut BytesMut) {
+    if src.remaining() >= size_of::<u16>() {
+        let size = src.get_u16_le() as usize;
+        src.advance(size.min(src.remaining()));
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
-#[repr(u16)]
 pub enum MessageId {
-    Ping = 0,
-    PingResponse = 1,
-    Request = 2,
-    Response = 3,
-    RequestModelDef = 4,
-    ModelDef = 5,
-    RequestFrameData = 6,
-    FrameData = 7,
-    MessageString = 8,
-    Disconnect = 9,
-    KeepAlive = 10,
-    DisconnectByTimeout = 11,
-    EchoRequest = 12,
-    EchoResponse = 13,
-    Discovery = 14,
-    Unrecognized = 100,
+    Ping,
+    PingResponse,
+    Request,
+    Response,
+    RequestModelDef,
+    ModelDef,
+    RequestFrameData,
+    FrameData,
+    MessageString,
+    Disconnect,
+    KeepAlive,
+    DisconnectByTimeout,
+    EchoRequest,
+    EchoResponse,
+    Discovery,
+    // keeps the raw id of messages this crate doesn't know about
+    Unrecognized(u16),
 }
 
 impl From<&[u8; 2]> for MessageId {
@@ -162,14 +759,64 @@ impl From<u16> for MessageId {
             12 => Self::EchoRequest,
             13 => Self::EchoResponse,
             14 => Self::Discovery,
-            _ => Self::Unrecognized,
+            value => Self::Unrecognized(value),
         }
     }
 }
 
+impl MessageId {
+    // usable in the const packet builders, unlike `u16::from`
+    pub const fn to_u16(self) -> u16 {
+        match self {
+            MessageId::Ping => 0,
+            MessageId::PingResponse => 1,
+            MessageId::Request => 2,
+            MessageId::Response => 3,
+            MessageId::RequestModelDef => 4,
+            MessageId::ModelDef => 5,
+            MessageId::RequestFrameData => 6,
+            MessageId::FrameData => 7,
+            MessageId::MessageString => 8,
+            MessageId::Disconnect => 9,
+            MessageId::KeepAlive => 10,
+            MessageId::DisconnectByTimeout => 11,
+            MessageId::EchoRequest => 12,
+            MessageId::EchoResponse => 13,
+            MessageId::Discovery => 14,
+            MessageId::Unrecognized(value) => value,
+        }
+    }
+}
+
+impl From<MessageId> for u16 {
+    fn from(id: MessageId) -> Self {
+        id.to_u16()
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct PingResponseCodec;
 
+impl Encoder<PingResponse> for PingResponseCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: PingResponse, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // the name is null-padded to a fixed 256 bytes and needs room for its terminator
+        let name = item.app_name.trim_end_matches('\0').as_bytes();
+        if name.len() >= 256 {
+            return Err(
+                format!("Application name is {} bytes, at most 255 fit", name.len()).into(),
+            );
+        }
+        dst.reserve(2 + SERVER_INFO_LEN);
+        dst.put_i16_le(item.packet_size);
+        dst.extend_from_slice(name);
+        dst.put_bytes(0, 256 - name.len());
+        dst.extend_from_slice(&item.server_version);
+        dst.extend_from_slice(&item.natnet_version);
+        Ok(())
+    }
+}
+
 impl Decoder for PingResponseCodec {
     type Item = PingResponse;
     type Error = Box<dyn std::error::Error>;
@@ -202,7 +849,7 @@ impl Decoder for PingResponseCodec {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct PingResponse {
     pub packet_size: i16,
     pub app_name: String,
@@ -210,196 +857,924 @@ pub struct PingResponse {
     pub natnet_version: [u8; 4],
 }
 
+// Application name, server version, and NatNet version
+const SERVER_INFO_LEN: usize = 264;
+
+// Responses carry no type tag, so the payload length decides how they're read:
+// - 4 bytes is a number from e.g. GetProperty.  Small magnitudes are integers (as floats they'd
+//   be subnormal), anything else that is a normal float is a float, and the rest are integers.
+// - at least the size of the server info block is a ServerInfo
+// - anything else is a null-terminated string
 #[derive(Debug, Default)]
-pub struct FrameDataCodec;
+pub struct ResponseCodec;
 
-impl Encoder<FrameData> for FrameDataCodec {
+impl Decoder for ResponseCodec {
+    type Item = Response;
     type Error = Box<dyn std::error::Error>;
-    fn encode(&mut self, item: FrameData, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least message id, packet size, frame number, all counts,
-        // timecodes, timestamps, and frame parameters
-        //dst.reserve(78);
-        dst.extend_from_slice(&item.packet_size.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.frame_number.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.markerset_count.to_le_bytes()[..]);
-        let mut markerset_codec = MarkerSetCodec::default();
-        for ms in item.markersets.into_iter() {
-            markerset_codec.encode(ms, dst)?;
-        }
-        dst.extend_from_slice(&item.unlabeled_marker_count.to_le_bytes()[..]);
-        for pos in item.unlabeled_marker_positions.into_iter() {
-            dst.extend_from_slice(&pos.x.to_le_bytes()[..]);
-            dst.extend_from_slice(&pos.y.to_le_bytes()[..]);
-            dst.extend_from_slice(&pos.z.to_le_bytes()[..]);
-        }
-        dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
-        let mut rigid_body_codec = RigidBodyCodec::default();
-        for rb in item.rigid_bodies.into_iter() {
-            rigid_body_codec.encode(rb, dst)?;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        if src.remaining() < 2 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "Response",
+                expected: 2,
+                got: src.remaining(),
+            }
+            .into());
         }
-        dst.extend_from_slice(&item.skeleton_count.to_le_bytes()[..]);
-        let mut skeleton_codec = SkeletonCodec::default();
-        for skeleton in item.skeletons.into_iter() {
-            skeleton_codec.encode(skeleton, dst)?;
+        let packet_size = u16::from_le_bytes([src[0], src[1]]) as usize;
+        log::debug!("Packet Size: {} bytes", packet_size);
+        if packet_size >= SERVER_INFO_LEN {
+            let server_info = PingResponseCodec.decode(src)?;
+            return Ok(Response::ServerInfo(server_info));
         }
-        dst.extend_from_slice(&item.labeled_marker_count.to_le_bytes()[..]);
-        let mut labeled_marker_codec = LabeledMarkerCodec::default();
-        for lmp in item.labeled_marker_positions.into_iter() {
-            labeled_marker_codec.encode(lmp, dst)?;
+        src.advance(2);
+        if src.remaining() < packet_size {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "Response",
+                expected: packet_size,
+                got: src.remaining(),
+            }
+            .into());
         }
-        dst.extend_from_slice(&item.force_plate_count.to_le_bytes()[..]);
-        let mut force_plate_codec = ForcePlateCodec::default();
-        for fp in item.force_plates.into_iter() {
-            force_plate_codec.encode(fp, dst)?;
+        let mut payload = src.split_to(packet_size);
+        if packet_size == 4 {
+            let value = payload.get_i32_le();
+            let float = f32::from_bits(value as u32);
+            if value.unsigned_abs() > 1 << 24 && float.is_normal() {
+                return Ok(Response::Float(float));
+            }
+            return Ok(Response::Int(value));
         }
-        dst.extend_from_slice(&item.device_count.to_le_bytes()[..]);
-        let mut device_codec = DeviceCodec::default();
-        for device in item.devices.into_iter() {
-            device_codec.encode(device, dst)?;
+        let mut text_buf = Vec::new();
+        let _len = payload.reader().read_until(b'\0', &mut text_buf)?;
+        let text = String::from_utf8(text_buf)?;
+        log::debug!("Response text: {}", text);
+        Ok(Response::Text(text))
+    }
+}
+
+#[derive(Debug)]
+pub enum Response {
+    ServerInfo(PingResponse),
+    Int(i32),
+    Float(f32),
+    Text(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NatNetVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub build: u8,
+    pub revision: u8,
+}
+
+impl NatNetVersion {
+    pub const fn new(major: u8, minor: u8, build: u8, revision: u8) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+            revision,
         }
-        dst.extend_from_slice(&item.timecode.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timecode_sub.to_le_bytes()[..]);
-        let mut stamps_codec = StampsCodec::default();
-        stamps_codec.encode(item.stamps, dst)?;
-        let mut frame_parameters_codec = FrameParametersCodec::default();
-        frame_parameters_codec.encode(item.frame_parameters, dst)?;
-        Ok(())
     }
 }
 
-impl Decoder for FrameDataCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = FrameData;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let packet_size = src.get_u16_le();
-        log::debug!("Packet Size: {} bytes", packet_size);
-        let frame_number = src.get_u32_le();
-        log::debug!("Frame #: {}", frame_number);
-        let markerset_count = src.get_u32_le();
-        log::debug!("MarkerSet Count: {}", markerset_count);
-        let markerset_bytes = src.get_u32_le();
-        log::debug!("MarkerSet Bytes: {}", markerset_bytes);
-        let mut markerset_codec = MarkerSetCodec::default();
-        let markersets: Vec<MarkerSet> = (0..markerset_count)
-            .map(|_| markerset_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("MarkerSets: {:?}", markersets);
-        let unlabeled_marker_count = src.get_u32_le();
-        log::debug!("Unlabeled Marker Count: {}", unlabeled_marker_count);
-        let unlabeled_marker_bytes = src.get_u32_le();
-        log::debug!("Unlabeled Marker Bytes: {}", unlabeled_marker_bytes);
-        let unlabeled_marker_positions: Vec<Vec3> = (0..unlabeled_marker_count)
-            .map(|_| Vec3 {
-                x: src.get_f32_le(),
-                y: src.get_f32_le(),
-                z: src.get_f32_le(),
-            })
-            .collect();
-        log::debug!(
-            "Unlabeled Marker Positions: {:?}",
-            unlabeled_marker_positions
-        );
-        let rigid_body_count = src.get_u32_le();
-        log::debug!("RigidBody Count: {}", rigid_body_count);
-        let rigid_body_bytes = src.get_u32_le();
-        log::debug!("RigidBody Bytes: {}", rigid_body_bytes);
-        let mut rigid_body_codec = RigidBodyCodec::default();
-        let rigid_bodies: Vec<RigidBody> = (0..rigid_body_count)
-            .map(|_| rigid_body_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("RigidBodies: {:?}", rigid_bodies);
+impl From<[u8; 4]> for NatNetVersion {
+    fn from(value: [u8; 4]) -> Self {
+        Self::new(value[0], value[1], value[2], value[3])
+    }
+}
+
+impl fmt::Display for NatNetVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+const FRAME_SECTION_SIZE_VERSION: NatNetVersion = NatNetVersion::new(4, 1, 0, 0);
+
+#[derive(Debug, Copy, Clone)]
+pub struct FrameDataCodec {
+    pub version: NatNetVersion,
+    // reject corrupt values (e.g. non-finite poses) instead of passing them through
+    pub strict: bool,
+    // keep each section's raw bytes in `FrameData::raw_sections` for pass-through encoding
+    pub keep_raw_sections: bool,
+}
+
+impl FrameDataCodec {
+    pub fn new(version: NatNetVersion) -> Self {
+        Self {
+            version,
+            strict: false,
+            keep_raw_sections: false,
+        }
+    }
+
+    // Section byte counts and the asset section both appeared in NatNet 4.1
+    fn has_section_bytes(&self) -> bool {
+        self.version >= FRAME_SECTION_SIZE_VERSION
+    }
+
+    fn has_assets(&self) -> bool {
+        self.version >= FRAME_SECTION_SIZE_VERSION
+    }
+
+    fn section_bytes(&self, src: &mut BytesMut) -> Option<u32> {
+        self.has_section_bytes().then(|| src.get_u32_le())
+    }
+
+    // Sections are only captured when their byte count is known
+    fn capture_section(&self, src: &BytesMut, section_bytes: Option<u32>) -> Option<Bytes> {
+        let section_bytes = section_bytes?;
+        self.keep_raw_sections
+            .then(|| Bytes::copy_from_slice(&src[..src.len().min(section_bytes as usize)]))
+    }
+
+    fn start_section(&self, dst: &mut BytesMut) -> Option<usize> {
+        self.has_section_bytes().then(|| start_section(dst))
+    }
+
+    fn end_section(&self, dst: &mut BytesMut, section_start: Option<usize>) {
+        if let Some(section_start) = section_start {
+            end_section(dst, section_start);
+        }
+    }
+
+    // Cheaply checks that `src` holds every section its byte counts declare, without decoding or
+    // allocating any elements, so truncated datagrams can be dropped up front
+    pub fn precheck(src: &BytesMut) -> Result<(), DecodeError> {
+        const SECTIONS: [&str; 8] = [
+            "MarkerSet section",
+            "Unlabeled Marker section",
+            "RigidBody section",
+            "Skeleton section",
+            "Asset section",
+            "LabeledMarker section",
+            "ForcePlate section",
+            "Device section",
+        ];
+        let mut buf = &src[..];
+        if buf.len() < 6 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "FrameData",
+                expected: 6,
+                got: buf.len(),
+            });
+        }
+        let packet_size = buf.get_u16_le() as usize;
+        buf.advance(4);
+        for item in SECTIONS {
+            if buf.len() < 8 {
+                return Err(DecodeError::NotEnoughBytes {
+                    item,
+                    expected: 8,
+                    got: buf.len(),
+                });
+            }
+            buf.advance(4);
+            let section_bytes = buf.get_u32_le() as usize;
+            if buf.len() < section_bytes {
+                return Err(DecodeError::NotEnoughBytes {
+                    item,
+                    expected: section_bytes,
+                    got: buf.len(),
+                });
+            }
+            buf.advance(section_bytes);
+        }
+        if buf.len() < 8 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "Timecode",
+                expected: 8,
+                got: buf.len(),
+            });
+        }
+        // packet size excludes the message id and the packet size field itself
+        let declared = src.len() - buf.len() + 8 - 2;
+        if declared > packet_size {
+            return Err(DecodeError::Invalid {
+                reason: format!(
+                    "FrameData sections need {} bytes but packet size is {}",
+                    declared, packet_size
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    // Guesses the oldest NatNet version whose FrameData layout matches `src` (starting at the
+    // packet size, as passed to `decode`) for passive captures that missed the handshake.  Section
+    // byte counts appeared in 4.1, and rigid bodies stopped embedding their markers in 3.0.
+    pub fn detect_version(src: &BytesMut) -> Option<NatNetVersion> {
+        if Self::has_section_sizes(src).unwrap_or(false) {
+            return Some(NatNetVersion::new(4, 1, 0, 0));
+        }
+        // skip packet size and frame number
+        let mut buf = src.get(6..)?;
+        let markerset_count = take_u32(&mut buf)?;
+        skip_markersets(&mut buf, markerset_count)?;
+        let unlabeled_marker_count = take_u32(&mut buf)?;
+        skip_bytes(&mut buf, unlabeled_marker_count as usize * 12)?;
+        let rigid_body_count = take_u32(&mut buf)?;
+        if rigid_body_count == 0 {
+            return Some(NatNetVersion::new(3, 0, 0, 0));
+        }
+        // the mean marker error of a 3.0+ rigid body sits where older versions put a small marker
+        // count, and a float error is never a small integer when read as one
+        let mut after_pose = buf.get(32..)?;
+        match take_u32(&mut after_pose)? {
+            1..=1000 => Some(NatNetVersion::new(2, 0, 0, 0)),
+            _ => Some(NatNetVersion::new(3, 0, 0, 0)),
+        }
+    }
+
+    fn has_section_sizes(src: &BytesMut) -> Option<bool> {
+        let mut buf = src.get(6..)?;
+        let markerset_count = take_u32(&mut buf)?;
+        let markerset_bytes = take_u32(&mut buf)? as usize;
+        let before = buf.len();
+        skip_markersets(&mut buf, markerset_count)?;
+        if before - buf.len() != markerset_bytes {
+            return Some(false);
+        }
+        let unlabeled_marker_count = take_u32(&mut buf)? as usize;
+        let unlabeled_marker_bytes = take_u32(&mut buf)? as usize;
+        // positions, optionally followed by ids
+        if unlabeled_marker_bytes != unlabeled_marker_count * 12
+            && unlabeled_marker_bytes != unlabeled_marker_count * 16
+        {
+            return Some(false);
+        }
+        skip_bytes(&mut buf, unlabeled_marker_bytes)?;
+        let rigid_body_count = take_u32(&mut buf)? as usize;
+        let rigid_body_bytes = take_u32(&mut buf)? as usize;
+        Some(rigid_body_bytes == rigid_body_count * 38)
+    }
+
+    // Fast path for consumers that only track rigid bodies: uses the section byte counts to skip
+    // the marker sections and stops after the rigid bodies, leaving `src` at the skeleton section
+    pub fn decode_rigid_bodies_only(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<(u32, Vec<RigidBody>), Box<dyn error::Error>> {
+        let mut rigid_bodies = Vec::new();
+        let (frame_number, consumed) =
+            self.for_each_rigid_body(src, |rb| rigid_bodies.push(rb.clone()))?;
+        src.advance(consumed);
+        Ok((frame_number, rigid_bodies))
+    }
+
+    // Visits each rigid body of the frame in `src` (starting at the packet size, as passed to
+    // `decode`) without building a `FrameData`.  Returns the frame number and the bytes read.
+    pub fn for_each_rigid_body<F: FnMut(&RigidBody)>(
+        &self,
+        src: &[u8],
+        mut f: F,
+    ) -> Result<(u32, usize), Box<dyn error::Error>> {
+        let mut buf = src;
+        // packet size, frame number, and the markerset count and byte count
+        if buf.remaining() < 14 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "FrameData",
+                expected: 14,
+                got: buf.remaining(),
+            }
+            .into());
+        }
+        buf.advance(2);
+        let frame_number = buf.get_u32_le();
+        for item in ["MarkerSet section", "Unlabeled Marker section"] {
+            if buf.remaining() < 8 {
+                return Err(DecodeError::NotEnoughBytes {
+                    item,
+                    expected: 8,
+                    got: buf.remaining(),
+                }
+                .into());
+            }
+            buf.advance(4);
+            let section_bytes = buf.get_u32_le() as usize;
+            if buf.remaining() < section_bytes {
+                return Err(DecodeError::NotEnoughBytes {
+                    item,
+                    expected: section_bytes,
+                    got: buf.remaining(),
+                }
+                .into());
+            }
+            buf.advance(section_bytes);
+        }
+        if buf.remaining() < 8 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "RigidBody section",
+                expected: 8,
+                got: buf.remaining(),
+            }
+            .into());
+        }
+        let rigid_body_count = buf.get_u32_le();
+        let _rigid_body_bytes = buf.get_u32_le();
+        let rigid_body_codec = RigidBodyCodec {
+            strict: self.strict,
+        };
+        for _ in 0..rigid_body_count {
+            f(&rigid_body_codec.decode_buf(&mut buf)?);
+        }
+        Ok((frame_number, src.len() - buf.len()))
+    }
+}
+
+fn take_u32(buf: &mut &[u8]) -> Option<u32> {
+    (buf.len() >= 4).then(|| buf.get_u32_le())
+}
+
+fn skip_bytes(buf: &mut &[u8], len: usize) -> Option<()> {
+    (buf.len() >= len).then(|| buf.advance(len))
+}
+
+fn skip_markersets(buf: &mut &[u8], count: u32) -> Option<()> {
+    for _ in 0..count {
+        let name_len = buf.iter().position(|&b| b == 0)?;
+        skip_bytes(buf, name_len + 1)?;
+        let marker_count = take_u32(buf)?;
+        skip_bytes(buf, marker_count as usize * 12)?;
+    }
+    Some(())
+}
+
+impl Default for FrameDataCodec {
+    fn default() -> Self {
+        Self::new(VERSION.into())
+    }
+}
+
+impl Encoder<FrameData> for FrameDataCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: FrameData, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least packet size, frame number, all counts and byte
+        // counts, timecodes, timestamps, and frame parameters
+        dst.reserve(112);
+        dst.extend_from_slice(&item.packet_size.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.frame_number.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.markerset_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.markersets {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut markerset_codec = MarkerSetCodec::default();
+            for ms in item.markersets.into_iter() {
+                markerset_codec.encode(ms, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.unlabeled_marker_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.unlabeled_markers {
+            dst.extend_from_slice(&raw);
+        } else {
+            for pos in item.unlabeled_marker_positions.into_iter() {
+                dst.extend_from_slice(&pos.x.to_le_bytes()[..]);
+                dst.extend_from_slice(&pos.y.to_le_bytes()[..]);
+                dst.extend_from_slice(&pos.z.to_le_bytes()[..]);
+            }
+            // the ids can only be told apart from the next section by the section byte count
+            if self.has_section_bytes() {
+                for id in item.unlabeled_marker_ids.into_iter() {
+                    dst.put_u32_le(id);
+                }
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.rigid_bodies {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut rigid_body_codec = RigidBodyCodec::default();
+            for rb in item.rigid_bodies.into_iter() {
+                rigid_body_codec.encode(rb, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.skeleton_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.skeletons {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut skeleton_codec = SkeletonCodec::default();
+            for skeleton in item.skeletons.into_iter() {
+                skeleton_codec.encode(skeleton, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        if self.has_assets() {
+            dst.extend_from_slice(&item.asset_count.to_le_bytes()[..]);
+            let section_start = self.start_section(dst);
+            if let Some(raw) = item.raw_sections.assets {
+                dst.extend_from_slice(&raw);
+            } else {
+                let mut asset_codec = AssetCodec::default();
+                for asset in item.assets.into_iter() {
+                    asset_codec.encode(asset, dst)?;
+                }
+            }
+            self.end_section(dst, section_start);
+        }
+        dst.extend_from_slice(&item.labeled_marker_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.labeled_markers {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut labeled_marker_codec = LabeledMarkerCodec::default();
+            for lmp in item.labeled_marker_positions.into_iter() {
+                labeled_marker_codec.encode(lmp, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.force_plate_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.force_plates {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut force_plate_codec = ForcePlateCodec::default();
+            for fp in item.force_plates.into_iter() {
+                force_plate_codec.encode(fp, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.device_count.to_le_bytes()[..]);
+        let section_start = self.start_section(dst);
+        if let Some(raw) = item.raw_sections.devices {
+            dst.extend_from_slice(&raw);
+        } else {
+            let mut device_codec = DeviceCodec::default();
+            for device in item.devices.into_iter() {
+                device_codec.encode(device, dst)?;
+            }
+        }
+        self.end_section(dst, section_start);
+        dst.extend_from_slice(&item.timecode.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timecode_sub.to_le_bytes()[..]);
+        let mut stamps_codec = StampsCodec::default();
+        stamps_codec.encode(item.stamps, dst)?;
+        let mut frame_parameters_codec = FrameParametersCodec::default();
+        frame_parameters_codec.encode(item.frame_parameters, dst)?;
+        if let Some(timestamp) = item.precision_transmit_timestamp {
+            dst.put_u64_le(timestamp);
+        }
+        Ok(())
+    }
+}
+
+// Writes a placeholder section byte count and returns the offset of the section body
+fn start_section(dst: &mut BytesMut) -> usize {
+    dst.put_u32_le(0);
+    dst.len()
+}
+
+// Backfills the section byte count written by `start_section`
+fn end_section(dst: &mut BytesMut, section_start: usize) {
+    let section_bytes = (dst.len() - section_start) as u32;
+    dst[section_start - 4..section_start].copy_from_slice(&section_bytes.to_le_bytes());
+}
+
+impl Decoder for FrameDataCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = FrameData;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut frame = FrameData::default();
+        self.decode_into(src, &mut frame)?;
+        Ok(frame)
+    }
+}
+
+impl FrameDataCodec {
+    // Decodes into an existing frame, reusing its vectors down to the nested ones (markerset
+    // names, skeleton and asset bodies, asset markers, force plate and device channels) so a
+    // steady stream decodes without allocating.  Only `keep_raw_sections` still copies each
+    // frame.  On error `frame` is left partially updated.
+    pub fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        frame: &mut FrameData,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.decode_frame(src, frame, &mut DecodeDiagnostics::default())
+    }
+
+    // Like `decode`, but also returns the problems lenient decoding would otherwise only log
+    pub fn decode_with_diagnostics(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<(FrameData, DecodeDiagnostics), Box<dyn error::Error>> {
+        let mut frame = FrameData::default();
+        let mut diagnostics = DecodeDiagnostics::default();
+        self.decode_frame(src, &mut frame, &mut diagnostics)?;
+        Ok((frame, diagnostics))
+    }
+
+    fn decode_frame(
+        &mut self,
+        src: &mut BytesMut,
+        frame: &mut FrameData,
+        diagnostics: &mut DecodeDiagnostics,
+    ) -> Result<(), Box<dyn error::Error>> {
+        if src.remaining() < 2 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "FrameData packet size",
+                expected: 2,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let packet_size = src.get_u16_le();
+        log::debug!("Packet Size: {} bytes", packet_size);
+        // The body is bounded by the packet size so the optional suffix fields never read into a
+        // following message, and suffix fields newer than this decoder (NatNet doesn't prefix the
+        // suffix with its length) are dropped with the rest of the body.  A zero packet size, as
+        // the encoder writes for a default frame, or one reaching past the buffer (e.g. a capture
+        // cut short) leaves the whole buffer to the decoder.
+        let body_len = packet_size as usize;
+        let bounded = body_len != 0 && src.remaining() >= body_len;
+        if bounded && src.remaining() > body_len {
+            let mut body = src.split_to(body_len);
+            return self.decode_body(&mut body, packet_size, frame, diagnostics);
+        }
+        self.decode_body(src, packet_size, frame, diagnostics)?;
+        if bounded && src.has_remaining() {
+            log::debug!("Skipping {} unknown suffix bytes", src.remaining());
+            src.advance(src.remaining());
+        }
+        Ok(())
+    }
+
+    fn decode_body(
+        &mut self,
+        src: &mut BytesMut,
+        packet_size: u16,
+        frame: &mut FrameData,
+        diagnostics: &mut DecodeDiagnostics,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let frame_number = src.get_u32_le();
+        log::debug!("Frame #: {}", frame_number);
+        let markerset_count = src.get_u32_le();
+        log::debug!("MarkerSet Count: {}", markerset_count);
+        let markerset_bytes = self.section_bytes(src);
+        log::debug!("MarkerSet Bytes: {:?}", markerset_bytes);
+        // each markerset is at least a nul terminated name and a marker count
+        if let Some(markerset_bytes) = markerset_bytes {
+            if (markerset_count == 0) != (markerset_bytes == 0)
+                || u64::from(markerset_bytes) < u64::from(markerset_count) * 5
+            {
+                return Err(DecodeError::InconsistentSection {
+                    section: "markerset",
+                }
+                .into());
+            }
+        }
+        let raw_markersets = self.capture_section(src, markerset_bytes);
+        let section_start = src.remaining();
+        let mut markerset_codec = MarkerSetCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut markerset_codec,
+            src,
+            markerset_count,
+            &mut frame.markersets,
+        )?;
+        let markerset_bytes = self.check_section(
+            "MarkerSet section",
+            markerset_bytes,
+            section_start - src.remaining(),
+            diagnostics,
+        )?;
+        log::debug!("MarkerSets: {:?}", frame.markersets);
+        let unlabeled_marker_count = src.get_u32_le();
+        log::debug!("Unlabeled Marker Count: {}", unlabeled_marker_count);
+        let unlabeled_marker_bytes = self.section_bytes(src);
+        log::debug!("Unlabeled Marker Bytes: {:?}", unlabeled_marker_bytes);
+        let raw_unlabeled_markers = self.capture_section(src, unlabeled_marker_bytes);
+        frame.unlabeled_marker_positions.clear();
+        frame
+            .unlabeled_marker_positions
+            .extend((0..unlabeled_marker_count).map(|_| Vec3 {
+                x: src.get_f32_le(),
+                y: src.get_f32_le(),
+                z: src.get_f32_le(),
+            }));
+        if self.strict {
+            ensure_finite(
+                "UnlabeledMarker.pos",
+                frame
+                    .unlabeled_marker_positions
+                    .iter()
+                    .all(|p| p.is_finite()),
+            )?;
+        }
+        // trailing ids are recognized by a section 4 bytes per marker longer than the positions
+        frame.unlabeled_marker_ids.clear();
+        let ids_bytes = u64::from(unlabeled_marker_count) * 16;
+        if unlabeled_marker_count > 0 && unlabeled_marker_bytes.map(u64::from) == Some(ids_bytes) {
+            frame
+                .unlabeled_marker_ids
+                .extend((0..unlabeled_marker_count).map(|_| src.get_u32_le()));
+        }
+        log::debug!("Unlabeled Marker Ids: {:?}", frame.unlabeled_marker_ids);
+        let unlabeled_marker_bytes =
+            unlabeled_marker_bytes.unwrap_or(unlabeled_marker_count.saturating_mul(12));
+        log::debug!(
+            "Unlabeled Marker Positions: {:?}",
+            frame.unlabeled_marker_positions
+        );
+        let rigid_body_count = src.get_u32_le();
+        log::debug!("RigidBody Count: {}", rigid_body_count);
+        let rigid_body_bytes = self.section_bytes(src);
+        log::debug!("RigidBody Bytes: {:?}", rigid_body_bytes);
+        let raw_rigid_bodies = self.capture_section(src, rigid_body_bytes);
+        let section_start = src.remaining();
+        let mut rigid_body_codec = RigidBodyCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut rigid_body_codec,
+            src,
+            rigid_body_count,
+            &mut frame.rigid_bodies,
+        )?;
+        let rigid_body_bytes = self.check_section(
+            "RigidBody section",
+            rigid_body_bytes,
+            section_start - src.remaining(),
+            diagnostics,
+        )?;
+        log::debug!("RigidBodies: {:?}", frame.rigid_bodies);
         let skeleton_count = src.get_u32_le();
         log::debug!("Skeleton Count: {}", skeleton_count);
-        let skeleton_bytes = src.get_u32_le();
-        log::debug!("Skeleton Bytes: {}", skeleton_bytes);
-        let mut skeleton_codec = SkeletonCodec::default();
-        let skeletons: Vec<Skeleton> = (0..skeleton_count)
-            .map(|_| skeleton_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Skeletons: {:?}", skeletons);
-        let asset_count = src.get_u32_le();
-        log::debug!("Asset Count: {}", asset_count);
-        let asset_bytes = src.get_u32_le();
-        log::debug!("Asset Bytes: {}", asset_bytes);
-        let mut asset_codec = AssetCodec::default();
-        let assets: Vec<Asset> = (0..asset_count)
-            .map(|_| asset_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Assets: {:?}", assets);
+        let skeleton_bytes = self.section_bytes(src);
+        log::debug!("Skeleton Bytes: {:?}", skeleton_bytes);
+        let raw_skeletons = self.capture_section(src, skeleton_bytes);
+        let section_start = src.remaining();
+        let mut skeleton_codec = SkeletonCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut skeleton_codec,
+            src,
+            skeleton_count,
+            &mut frame.skeletons,
+        )?;
+        let skeleton_bytes = self.check_section(
+            "Skeleton section",
+            skeleton_bytes,
+            section_start - src.remaining(),
+            diagnostics,
+        )?;
+        log::debug!("Skeletons: {:?}", frame.skeletons);
+        // older servers go straight from skeletons to labeled markers
+        let (asset_count, asset_bytes, raw_assets) = if self.has_assets() {
+            let asset_count = src.get_u32_le();
+            log::debug!("Asset Count: {}", asset_count);
+            let asset_bytes = self.section_bytes(src);
+            log::debug!("Asset Bytes: {:?}", asset_bytes);
+            let raw_assets = self.capture_section(src, asset_bytes);
+            let section_start = src.remaining();
+            let mut asset_codec = AssetCodec {
+                strict: self.strict,
+            };
+            decode_elements_into(&mut asset_codec, src, asset_count, &mut frame.assets)?;
+            let consumed = (section_start - src.remaining()) as u32;
+            (asset_count, asset_bytes.unwrap_or(consumed), raw_assets)
+        } else {
+            frame.assets.clear();
+            (0, 0, None)
+        };
+        log::debug!("Assets: {:?}", frame.assets);
         let labeled_marker_count = src.get_u32_le();
         log::debug!("Labeled Marker Count: {}", labeled_marker_count);
-        let labeled_marker_bytes = src.get_u32_le();
-        log::debug!("Labeled Marker Bytes: {}", labeled_marker_bytes);
-        let mut labeled_marker_codec = LabeledMarkerCodec::default();
-        let labeled_marker_positions: Vec<LabeledMarker> = (0..labeled_marker_count)
-            .map(|_| labeled_marker_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Labeled Marker Positions: {:?}", labeled_marker_positions);
+        let labeled_marker_bytes = self.section_bytes(src);
+        log::debug!("Labeled Marker Bytes: {:?}", labeled_marker_bytes);
+        let raw_labeled_markers = self.capture_section(src, labeled_marker_bytes);
+        let section_start = src.remaining();
+        let mut labeled_marker_codec = LabeledMarkerCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut labeled_marker_codec,
+            src,
+            labeled_marker_count,
+            &mut frame.labeled_marker_positions,
+        )?;
+        let labeled_marker_bytes = self.check_section(
+            "LabeledMarker section",
+            labeled_marker_bytes,
+            section_start - src.remaining(),
+            diagnostics,
+        )?;
+        log::debug!(
+            "Labeled Marker Positions: {:?}",
+            frame.labeled_marker_positions
+        );
         let force_plate_count = src.get_u32_le();
         log::debug!("Force Plate Count: {}", force_plate_count);
-        let force_plate_bytes = src.get_u32_le();
-        log::debug!("Force Plate Bytes: {}", force_plate_bytes);
+        let force_plate_bytes = self.section_bytes(src);
+        log::debug!("Force Plate Bytes: {:?}", force_plate_bytes);
+        let raw_force_plates = self.capture_section(src, force_plate_bytes);
+        let section_start = src.remaining();
         let mut force_plate_codec = ForcePlateCodec::default();
-        let force_plates: Vec<ForcePlate> = (0..force_plate_count)
-            .map(|_| force_plate_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Force Plates: {:?}", force_plates);
+        decode_elements_into(
+            &mut force_plate_codec,
+            src,
+            force_plate_count,
+            &mut frame.force_plates,
+        )?;
+        let force_plate_bytes =
+            force_plate_bytes.unwrap_or((section_start - src.remaining()) as u32);
+        log::debug!("Force Plates: {:?}", frame.force_plates);
         let device_count = src.get_u32_le();
         log::debug!("Device Count: {}", device_count);
-        let device_bytes = src.get_u32_le();
-        log::debug!("Device Bytes: {}", device_bytes);
-        let mut device_codec = DeviceCodec::default();
-        let devices: Vec<Device> = (0..device_count)
-            .map(|_| device_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Devices: {:?}", devices);
+        let device_bytes = self.section_bytes(src);
+        log::debug!("Device Bytes: {:?}", device_bytes);
+        let raw_devices = self.capture_section(src, device_bytes);
+        // only treat devices as tagged when the untagged layout can't fill the section but the
+        // tagged one does exactly, which needs the section byte count
+        let has_device_type = device_bytes.is_some_and(|device_bytes| {
+            let device_section = &src[..src.len().min(device_bytes as usize)];
+            let fills_section = |codec: &DeviceCodec| {
+                codec.section_len(device_section, device_count) == Some(device_bytes as usize)
+            };
+            let tagged = DeviceCodec {
+                has_device_type: true,
+            };
+            device_count > 0 && !fills_section(&DeviceCodec::default()) && fills_section(&tagged)
+        });
+        let section_start = src.remaining();
+        let mut device_codec = DeviceCodec { has_device_type };
+        decode_elements_into(&mut device_codec, src, device_count, &mut frame.devices)?;
+        let device_bytes = device_bytes.unwrap_or((section_start - src.remaining()) as u32);
+        log::debug!("Devices: {:?}", frame.devices);
         let timecode = src.get_u32_le();
         log::debug!("TimeCode: {}", timecode);
         let timecode_sub = src.get_u32_le();
         log::debug!("TimeCode Sub: {}", timecode_sub);
+        // the stamps and suffix are absent on some servers, so only default them when the
+        // buffer is exhausted; a partial tail is a truncated frame
         let mut stamps_codec = StampsCodec::default();
-        let stamps: Stamps = stamps_codec.decode(src).unwrap_or_default();
+        let stamps = if src.has_remaining() {
+            stamps_codec.decode(src)?
+        } else {
+            log::debug!("Frame has no stamps");
+            Stamps::default()
+        };
         log::debug!("Stamps: {:?}", stamps);
         let mut frame_parameters_codec = FrameParametersCodec::default();
-        let frame_parameters: FrameParameters =
-            frame_parameters_codec.decode(src).unwrap_or_default();
+        let frame_parameters = if src.has_remaining() {
+            frame_parameters_codec.decode(src)?
+        } else {
+            log::debug!("Frame has no suffix");
+            FrameParameters::default()
+        };
+        // shorter tails (e.g. the 4 byte end of data tag) aren't a timestamp
+        let precision_transmit_timestamp = (self.version >= NatNetVersion::new(3, 0, 0, 0)
+            && src.remaining() >= 8)
+            .then(|| src.get_u64_le());
+        log::debug!(
+            "Precision Transmit Timestamp: {:?}",
+            precision_transmit_timestamp
+        );
 
-        Ok(FrameData {
-            packet_size,
-            frame_number,
-            markerset_count,
-            markerset_bytes,
-            markersets,
-            unlabeled_marker_count,
-            unlabeled_marker_bytes,
-            unlabeled_marker_positions,
-            rigid_body_count,
-            rigid_body_bytes,
-            rigid_bodies,
-            skeleton_count,
-            skeleton_bytes,
-            skeletons,
-            labeled_marker_count,
-            labeled_marker_bytes,
-            labeled_marker_positions,
-            asset_count,
-            asset_bytes,
-            assets,
-            force_plate_count,
-            force_plate_bytes,
-            force_plates,
-            device_count,
-            device_bytes,
-            devices,
-            timecode,
-            timecode_sub,
-            stamps,
-            frame_parameters,
-        })
+        frame.packet_size = packet_size;
+        frame.frame_number = frame_number;
+        frame.markerset_count = markerset_count;
+        frame.markerset_bytes = markerset_bytes;
+        frame.unlabeled_marker_count = unlabeled_marker_count;
+        frame.unlabeled_marker_bytes = unlabeled_marker_bytes;
+        frame.rigid_body_count = rigid_body_count;
+        frame.rigid_body_bytes = rigid_body_bytes;
+        frame.skeleton_count = skeleton_count;
+        frame.skeleton_bytes = skeleton_bytes;
+        frame.labeled_marker_count = labeled_marker_count;
+        frame.labeled_marker_bytes = labeled_marker_bytes;
+        frame.asset_count = asset_count;
+        frame.asset_bytes = asset_bytes;
+        frame.force_plate_count = force_plate_count;
+        frame.force_plate_bytes = force_plate_bytes;
+        frame.device_count = device_count;
+        frame.device_bytes = device_bytes;
+        frame.timecode = timecode;
+        frame.timecode_sub = timecode_sub;
+        frame.stamps = stamps;
+        frame.frame_parameters = frame_parameters;
+        frame.precision_transmit_timestamp = precision_transmit_timestamp;
+        frame.raw_sections = RawSections {
+            markersets: raw_markersets,
+            unlabeled_markers: raw_unlabeled_markers,
+            rigid_bodies: raw_rigid_bodies,
+            skeletons: raw_skeletons,
+            assets: raw_assets,
+            labeled_markers: raw_labeled_markers,
+            force_plates: raw_force_plates,
+            devices: raw_devices,
+        };
+        Ok(())
+    }
+
+    // A mismatch means an element was mis-sized and everything after it is misaligned, which
+    // strict decoding rejects and lenient decoding only logs.  Returns the declared byte count, or
+    // the bytes consumed for versions without byte counts.
+    fn check_section(
+        &self,
+        section: &'static str,
+        section_bytes: Option<u32>,
+        consumed: usize,
+        diagnostics: &mut DecodeDiagnostics,
+    ) -> Result<u32, DecodeError> {
+        let Some(section_bytes) = section_bytes else {
+            return Ok(consumed as u32);
+        };
+        if consumed == section_bytes as usize {
+            return Ok(section_bytes);
+        }
+        let mismatch = DecodeError::SectionSizeMismatch {
+            section,
+            expected: section_bytes as usize,
+            got: consumed,
+        };
+        if self.strict {
+            return Err(mismatch);
+        }
+        log::warn!("{}", mismatch);
+        diagnostics.warnings.push(DecodeWarning {
+            section,
+            kind: DecodeWarningKind::SectionSizeMismatch,
+            detail: mismatch.to_string(),
+        });
+        Ok(section_bytes)
     }
 }
 
-#[derive(Debug, Clone)]
+// Problems found while decoding that lenient decoding tolerates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodeDiagnostics {
+    pub warnings: Vec<DecodeWarning>,
+}
+
+impl DecodeDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeWarning {
+    pub section: &'static str,
+    pub kind: DecodeWarningKind,
+    // the message that was also logged
+    pub detail: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DecodeWarningKind {
+    // a section's elements didn't fill the byte count its header declared
+    SectionSizeMismatch,
+}
+
+// Decodes an existing element in place, reusing any heap storage it owns
+trait DecodeInto: Decoder {
+    fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        item: &mut Self::Item,
+    ) -> Result<(), Self::Error> {
+        *item = self.decode(src)?;
+        Ok(())
+    }
+}
+
+// Decodes `count` elements into `items`, reusing the elements already there
+fn decode_elements_into<C: DecodeInto>(
+    codec: &mut C,
+    src: &mut BytesMut,
+    count: u32,
+    items: &mut Vec<C::Item>,
+) -> Result<(), C::Error> {
+    let count = count as usize;
+    // truncating keeps the capacity, so it settles at the largest count seen
+    items.truncate(count);
+    for item in items.iter_mut() {
+        codec.decode_into(src, item)?;
+    }
+    for _ in items.len()..count {
+        items.push(codec.decode(src)?);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FrameData {
     pub packet_size: u16,
     pub frame_number: u32,
@@ -409,6 +1784,8 @@ pub struct FrameData {
     pub unlabeled_marker_count: u32,
     pub unlabeled_marker_bytes: u32,
     pub unlabeled_marker_positions: Vec<Vec3>,
+    // Some servers follow the unlabeled positions with an id per marker.  Empty when they don't.
+    pub unlabeled_marker_ids: Vec<u32>,
     pub rigid_body_count: u32,
     pub rigid_body_bytes: u32,
     pub rigid_bodies: Vec<RigidBody>,
@@ -431,1084 +1808,1824 @@ pub struct FrameData {
     pub timecode_sub: u32,
     pub stamps: Stamps,
     pub frame_parameters: FrameParameters,
+    // High precision transmit time some 3.0+ servers append after the frame parameters
+    pub precision_transmit_timestamp: Option<u64>,
+    pub raw_sections: RawSections,
 }
 
-#[derive(Debug, Default)]
-pub struct ModelDefCodec;
+// Raw section bodies captured when decoding with `FrameDataCodec::keep_raw_sections`.  The encoder
+// writes a captured section verbatim instead of re-encoding it, so set a section to `None` after
+// modifying the corresponding decoded data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RawSections {
+    pub markersets: Option<Bytes>,
+    pub unlabeled_markers: Option<Bytes>,
+    pub rigid_bodies: Option<Bytes>,
+    pub skeletons: Option<Bytes>,
+    pub assets: Option<Bytes>,
+    pub labeled_markers: Option<Bytes>,
+    pub force_plates: Option<Bytes>,
+    pub devices: Option<Bytes>,
+}
 
-impl Decoder for ModelDefCodec {
-    type Item = ModelDef;
-    type Error = Box<dyn error::Error>;
+impl FrameData {
+    pub fn to_packet(&self) -> Result<BytesMut, Box<dyn error::Error>> {
+        let mut dst = BytesMut::new();
+        dst.put_u16_le(MessageId::FrameData.into());
+        let mut codec = FrameDataCodec::default();
+        codec.encode(self.clone(), &mut dst)?;
+        // packet size excludes the message id and the packet size field itself
+        let packet_size = u16::try_from(dst.len() - 4)?;
+        dst[2..4].copy_from_slice(&packet_size.to_le_bytes());
+        Ok(dst)
+    }
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let packet_size = src.get_u16_le();
-        log::debug!("Packet Size: {} bytes", packet_size);
-        let dataset_count = src.get_u32_le();
-        let mut dataset = Vec::new();
-        log::debug!("DataSet Count: {}", dataset_count);
-        for _ in 0..dataset_count {
-            let data_type = src.get_u32_le();
-            log::debug!("Data Type: {}", data_type);
-            let size = src.get_u32_le();
-            log::debug!("Data Size: {}", size);
-            let data = match data_type {
-                0 => {
-                    let mut codec = MarkerSetDescCodec;
-                    ModelDefData::MarkerSetDesc {
-                        size,
-                        data: Box::new(codec.decode(src)?),
-                    }
-                }
-                1 => {
-                    let mut codec = RigidBodyDescCodec;
-                    ModelDefData::RigidBodyDesc {
-                        size,
-                        data: Box::new(codec.decode(src)?),
-                    }
-                }
-                5 => {
-                    let mut codec = CameraDescCodec;
-                    ModelDefData::CameraDesc {
-                        size,
-                        data: Box::new(codec.decode(src)?),
-                    }
-                }
-                _ => unimplemented!(),
-            };
-            dataset.push(data);
+    // Compact encoding for archiving frames, unrelated to the NatNet wire format
+    #[cfg(feature = "postcard")]
+    pub fn to_postcard(&self) -> Vec<u8> {
+        // plain data serialized into a growable buffer can't fail
+        postcard::to_allocvec(self).expect("Failed to serialize FrameData")
+    }
+
+    #[cfg(feature = "postcard")]
+    pub fn from_postcard(src: &[u8]) -> Result<Self, Box<dyn error::Error>> {
+        Ok(postcard::from_bytes(src)?)
+    }
+
+    pub fn unlabeled_centroid(&self) -> Option<Vec3> {
+        centroid(&self.unlabeled_marker_positions)
+    }
+
+    // Markersets excluding the aggregate "all" set
+    pub fn named_markersets(&self) -> impl Iterator<Item = &MarkerSet> {
+        self.markersets.iter().filter(|ms| !ms.is_aggregate())
+    }
+
+    // A stable FNV-1a hash of the frame number, rigid body ids and poses, and marker ids and
+    // positions, leaving out timestamps and other per-hop metadata.  It's meant for spotting
+    // corruption between a sender and receiver; it is not a cryptographic hash.
+    pub fn content_digest(&self) -> u64 {
+        let mut digest = ContentDigest::default();
+        digest.write(&self.frame_number.to_le_bytes());
+        for rb in self.all_rigid_bodies() {
+            digest.write(&rb.id.to_le_bytes());
+            digest.write_f32s(&rb.pos.to_array());
+            digest.write_f32s(&rb.rot.to_array());
+        }
+        for ms in self.markersets.iter() {
+            digest.write(ms.name.as_bytes());
+            ms.positions
+                .iter()
+                .for_each(|pos| digest.write_f32s(&pos.to_array()));
         }
+        self.unlabeled_marker_positions
+            .iter()
+            .for_each(|pos| digest.write_f32s(&pos.to_array()));
+        for marker in self.labeled_marker_positions.iter() {
+            digest.write(&marker.id.to_le_bytes());
+            digest.write_f32s(&marker.pos.to_array());
+        }
+        digest.0
+    }
 
-        Ok(ModelDef {
-            packet_size,
-            dataset_count,
-            dataset,
-        })
+    // `FrameDataCodec::decode_into` never shrinks the frame's vectors, so their capacity stays at
+    // the largest frame seen.  Call this to give that memory back, e.g. after a burst of markers.
+    pub fn shrink_to_fit(&mut self) {
+        self.markersets.shrink_to_fit();
+        for ms in self.markersets.iter_mut() {
+            ms.positions.shrink_to_fit();
+        }
+        self.unlabeled_marker_positions.shrink_to_fit();
+        self.unlabeled_marker_ids.shrink_to_fit();
+        self.rigid_bodies.shrink_to_fit();
+        self.skeletons.shrink_to_fit();
+        for skeleton in self.skeletons.iter_mut() {
+            skeleton.rigid_bodies.shrink_to_fit();
+        }
+        self.assets.shrink_to_fit();
+        self.labeled_marker_positions.shrink_to_fit();
+        self.force_plates.shrink_to_fit();
+        self.devices.shrink_to_fit();
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct ModelDef {
-    pub packet_size: u16,
-    pub dataset_count: u32,
-    pub dataset: Vec<ModelDefData>,
-}
+    pub fn markerset(&self, name: &str) -> Option<&MarkerSet> {
+        self.markersets.iter().find(|ms| ms.name == name)
+    }
 
-#[derive(Debug, Clone)]
-pub enum ModelDefData {
-    MarkerSetDesc { size: u32, data: Box<MarkerSetDesc> },
-    RigidBodyDesc { size: u32, data: Box<RigidBodyDesc> },
-    SkeletonDesc,
-    ForcePlateDesc,
-    DeviceDesc,
-    CameraDesc { size: u32, data: Box<CameraDesc> },
-    AssetDesc,
-    Unknown,
-}
+    pub fn summary(&self) -> FrameSummary {
+        FrameSummary {
+            frame_number: self.frame_number,
+            markersets: self.markersets.len(),
+            markerset_bytes: self.markerset_bytes,
+            unlabeled_markers: self.unlabeled_marker_positions.len(),
+            unlabeled_marker_bytes: self.unlabeled_marker_bytes,
+            rigid_bodies: self.rigid_bodies.len(),
+            rigid_body_bytes: self.rigid_body_bytes,
+            skeletons: self.skeletons.len(),
+            skeleton_bytes: self.skeleton_bytes,
+            assets: self.assets.len(),
+            asset_bytes: self.asset_bytes,
+            labeled_markers: self.labeled_marker_positions.len(),
+            labeled_marker_bytes: self.labeled_marker_bytes,
+            force_plates: self.force_plates.len(),
+            force_plate_bytes: self.force_plate_bytes,
+            devices: self.devices.len(),
+            device_bytes: self.device_bytes,
+            total_markers: self.labeled_marker_positions.len()
+                + self.unlabeled_marker_positions.len(),
+        }
+    }
 
-#[derive(Debug, Default)]
-pub struct Vec3Codec;
+    // Converts every position and orientation in the frame without reallocating its vectors
+    pub fn convert_coords_in_place(&mut self, conv: CoordConversion) {
+        if conv == CoordConversion::Identity {
+            return;
+        }
+        for markerset in &mut self.markersets {
+            for pos in &mut markerset.positions {
+                *pos = conv.apply_vec3(*pos);
+            }
+        }
+        for pos in &mut self.unlabeled_marker_positions {
+            *pos = conv.apply_vec3(*pos);
+        }
+        for rb in &mut self.rigid_bodies {
+            rb.convert_coords_in_place(conv);
+        }
+        for skeleton in &mut self.skeletons {
+            for rb in &mut skeleton.rigid_bodies {
+                rb.convert_coords_in_place(conv);
+            }
+        }
+        for asset in &mut self.assets {
+            for rb in &mut asset.rigid_bodies {
+                rb.pos = conv.apply_vec3(rb.pos);
+                rb.rot = conv.apply_quat(rb.rot);
+            }
+            for pos in &mut asset.markers.marker_positions {
+                *pos = conv.apply_vec3(*pos);
+            }
+        }
+        for marker in &mut self.labeled_marker_positions {
+            marker.pos = conv.apply_vec3(marker.pos);
+        }
+    }
 
-impl Encoder<Vec3> for Vec3Codec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Vec3, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        dst.extend_from_slice(&bincode::serialize(&item)?);
-        Ok(())
+    // Set by Motive when rigid bodies, skeletons, or assets were added, removed, or renamed, so
+    // previously requested model definitions may be stale
+    pub fn tracking_models_changed(&self) -> bool {
+        self.frame_parameters.tracked_models_changed
     }
-}
 
-impl Decoder for Vec3Codec {
-    type Item = Vec3;
-    type Error = Box<dyn error::Error>;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        Ok(Vec3 {
-            x: src.get_f32_le(),
-            y: src.get_f32_le(),
-            z: src.get_f32_le(),
-        })
+    pub fn frame_id(&self) -> u32 {
+        self.frame_number
     }
-}
 
-#[derive(Debug, Default)]
-pub struct QuatCodec;
+    // Frames elapsed since `prev_frame_number`, counting across wraparound at u32::MAX
+    pub fn frames_since(&self, prev_frame_number: u32) -> u32 {
+        self.frame_number.wrapping_sub(prev_frame_number)
+    }
 
-impl Encoder<Quat> for QuatCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Quat, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        dst.extend_from_slice(&bincode::serialize(&item)?);
-        Ok(())
+    pub fn smpte_timecode(&self) -> SmpteTimecode {
+        SmpteTimecode::from_raw(self.timecode, self.timecode_sub)
     }
-}
 
-impl Decoder for QuatCodec {
-    type Item = Quat;
-    type Error = Box<dyn error::Error>;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        Ok(Quat::from_xyzw(
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
+    // The wire has no validity flag; Motive streams zeros for both fields when no timecode source
+    // is connected, so an all zero timecode is treated as missing.  A genuine 00:00:00:00 with no
+    // subframe can't be told apart.
+    pub fn has_valid_timecode(&self) -> bool {
+        self.timecode != 0 || self.timecode_sub != 0
+    }
+
+    pub fn with_names<'a>(&'a self, model: &'a ModelDef) -> Vec<(&'a str, &'a RigidBody)> {
+        self.rigid_bodies
+            .iter()
+            .map(|rb| {
+                let name = i32::try_from(rb.id)
+                    .ok()
+                    .and_then(|id| model.rigid_body_desc(id))
+                    .map(|desc| desc.name.as_str())
+                    .unwrap_or("");
+                (name, rb)
+            })
+            .collect()
+    }
+
+    pub fn assets_with_names<'a>(&'a self, model: &'a ModelDef) -> Vec<(&'a str, &'a Asset)> {
+        self.assets
+            .iter()
+            .map(|asset| {
+                let name = i32::try_from(asset.id)
+                    .ok()
+                    .and_then(|id| model.asset_desc(id))
+                    .map(|desc| desc.name.as_str())
+                    .unwrap_or("");
+                (name, asset)
+            })
+            .collect()
+    }
+
+    // Ids of the frame's rigid bodies, skeletons, and assets, in that order
+    pub fn all_ids(&self) -> Vec<StreamingId> {
+        self.rigid_bodies
+            .iter()
+            .map(|rb| StreamingId::RigidBody(rb.id))
+            .chain(self.skeletons.iter().map(|s| StreamingId::Skeleton(s.id)))
+            .chain(self.assets.iter().map(|a| StreamingId::Asset(a.id)))
+            .collect()
+    }
+
+    // Raw ids shared by more than one kind of streamed object, sorted
+    pub fn id_collisions(&self) -> Vec<u32> {
+        let mut kinds: HashMap<u32, StreamingId> = HashMap::new();
+        let mut collisions: Vec<u32> = self
+            .all_ids()
+            .into_iter()
+            .filter(|id| *kinds.entry(id.raw()).or_insert(*id) != *id)
+            .map(|id| id.raw())
+            .collect();
+        collisions.sort_unstable();
+        collisions.dedup();
+        collisions
+    }
+
+    // Standalone rigid bodies followed by every skeleton bone, in frame order
+    pub fn all_rigid_bodies(&self) -> impl Iterator<Item = &RigidBody> {
+        self.rigid_bodies.iter().chain(
+            self.skeletons
+                .iter()
+                .flat_map(|skeleton| skeleton.rigid_bodies.iter()),
         )
-        .normalize())
     }
-}
 
-/* Marker Asset */
+    pub fn all_labeled_and_unlabeled(&self) -> impl Iterator<Item = (Option<u32>, Vec3)> + '_ {
+        let labeled = self
+            .labeled_marker_positions
+            .iter()
+            .map(|m| (Some(m.id), m.as_point()));
+        let unlabeled = self
+            .unlabeled_marker_positions
+            .iter()
+            .map(|pos| (None, *pos));
+        labeled.chain(unlabeled)
+    }
 
-#[derive(Debug, Default)]
-pub struct MarkerAssetCodec {}
+    // Widened copies of `all_labeled_and_unlabeled` for consumers that accumulate in f64
+    pub fn positions_f64(&self) -> impl Iterator<Item = (Option<u32>, DVec3)> + '_ {
+        self.all_labeled_and_unlabeled()
+            .map(|(id, pos)| (id, pos.as_dvec3()))
+    }
 
-impl Encoder<MarkerAsset> for MarkerAssetCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: MarkerAsset, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // Reserve enough space for at least the id, rigid body count, and marker count
-        dst.reserve(3 * 8);
-        dst.extend_from_slice(&item.id.to_be_bytes());
-        if item.rigid_body_count != item.rigid_bodies.len() as u32 {
-            log::warn!(
-                "RigidBody count {} does not match length of rigid_bodies vec {}",
-                item.rigid_body_count,
-                item.rigid_bodies.len()
-            );
-            dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
-        } else {
-            dst.extend_from_slice(&(item.rigid_bodies.len() as u32).to_le_bytes()[..]);
-        }
-        let mut rigid_body_codec = RigidBodyCodec::default();
-        for rb in item.rigid_bodies.into_iter() {
-            rigid_body_codec.encode(rb, dst)?;
+    pub fn rigid_body_positions_f64(&self) -> impl Iterator<Item = (u32, DVec3)> + '_ {
+        self.rigid_bodies
+            .iter()
+            .map(|rb| (rb.id, rb.pos.as_dvec3()))
+    }
+
+    pub fn labeled_markers_by_model(&self) -> HashMap<u16, Vec<&LabeledMarker>> {
+        let mut models: HashMap<u16, Vec<&LabeledMarker>> = HashMap::new();
+        for marker in self.labeled_marker_positions.iter() {
+            models.entry(marker.model_id()).or_default().push(marker);
         }
-        item.marker_positions.iter().for_each(|p| {
-            dst.extend_from_slice(&p.x.to_le_bytes()[..]);
-            dst.extend_from_slice(&p.y.to_le_bytes()[..]);
-            dst.extend_from_slice(&p.z.to_le_bytes()[..]);
-        });
-        Ok(())
+        models
     }
-}
 
-impl Decoder for MarkerAssetCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = MarkerAsset;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let id = src.get_u32_le();
+    pub fn labeled_markers_larger_than(
+        &self,
+        min_size: f32,
+    ) -> impl Iterator<Item = &LabeledMarker> {
+        self.labeled_marker_positions
+            .iter()
+            .filter(move |m| m.size > min_size)
+    }
 
-        let rigid_body_count = src.get_u32_le();
-        let mut rigidbody_codec = RigidBodyCodec::default();
-        let rigid_bodies: Vec<RigidBody> = (0..rigid_body_count)
-            .map(|_| rigidbody_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        let marker_count = src.get_u32_le();
-        let marker_positions = (0..marker_count)
-            .map(|_| Vec3 {
-                x: src.get_f32_le(),
-                y: src.get_f32_le(),
-                z: src.get_f32_le(),
-            })
-            .collect();
+    // Markers Motive reconstructed from the point cloud.  Compared with `model_solved_count` it's a
+    // tracking quality signal, as model solved markers weren't actually seen.  The status flags
+    // are independent bits, so a marker with several set counts towards each.
+    pub fn point_cloud_solved_count(&self) -> usize {
+        self.labeled_marker_positions
+            .iter()
+            .filter(|m| m.is_point_cloud_solved())
+            .count()
+    }
 
-        Ok(Self::Item {
-            id,
-            rigid_body_count,
-            rigid_bodies,
-            marker_count,
-            marker_positions,
-        })
+    // Markers Motive filled in from a rigid body or skeleton model rather than seeing them,
+    // including occluded markers the model filled in
+    pub fn model_solved_count(&self) -> usize {
+        self.labeled_marker_positions
+            .iter()
+            .filter(|m| m.is_model_solved())
+            .count()
+    }
+
+    // Reports frame data that doesn't line up with `model`, e.g. after the model changed without
+    // requesting a new ModelDef
+    pub fn validate_against(&self, model: &ModelDef) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        for rb in self.rigid_bodies.iter() {
+            let known = i32::try_from(rb.id)
+                .ok()
+                .and_then(|id| model.rigid_body_desc(id))
+                .is_some();
+            if !known {
+                warnings.push(ValidationWarning::UnknownRigidBody { id: rb.id });
+            }
+        }
+        for ms in self.markersets.iter() {
+            match model.marker_set_desc(&ms.name) {
+                Some(desc) if desc.marker_count as usize != ms.positions.len() => {
+                    warnings.push(ValidationWarning::MarkerCountMismatch {
+                        name: ms.name.clone(),
+                        expected: desc.marker_count,
+                        got: ms.positions.len(),
+                    });
+                }
+                Some(_) => {}
+                None => warnings.push(ValidationWarning::UnknownMarkerSet {
+                    name: ms.name.clone(),
+                }),
+            }
+        }
+        warnings
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct MarkerAsset {
-    pub id: u32,
-    pub rigid_body_count: u32,
-    pub rigid_bodies: Vec<RigidBody>,
-    pub marker_count: u32,
-    pub marker_positions: Vec<Vec3>,
+// Decoded element counts and section byte counts of a frame, e.g. for metrics
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FrameSummary {
+    pub frame_number: u32,
+    pub markersets: usize,
+    pub markerset_bytes: u32,
+    pub unlabeled_markers: usize,
+    pub unlabeled_marker_bytes: u32,
+    pub rigid_bodies: usize,
+    pub rigid_body_bytes: u32,
+    pub skeletons: usize,
+    pub skeleton_bytes: u32,
+    pub assets: usize,
+    pub asset_bytes: u32,
+    pub labeled_markers: usize,
+    pub labeled_marker_bytes: u32,
+    pub force_plates: usize,
+    pub force_plate_bytes: u32,
+    pub devices: usize,
+    pub device_bytes: u32,
+    // labeled plus unlabeled markers
+    pub total_markers: usize,
 }
 
-/* MarkerSet */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationWarning {
+    UnknownRigidBody {
+        id: u32,
+    },
+    UnknownMarkerSet {
+        name: String,
+    },
+    MarkerCountMismatch {
+        name: String,
+        expected: i32,
+        got: usize,
+    },
+}
 
-#[derive(Debug, Default)]
-pub struct MarkerSetCodec {}
+/* StreamingId */
 
-impl Encoder<MarkerSet> for MarkerSetCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: MarkerSet, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the name, marker count, and a single position
-        dst.reserve(item.name.len() + 16);
-        dst.extend_from_slice(item.name.as_bytes());
-        // end string with null terminator
-        dst.put_u8(0);
-        if item.marker_count != item.positions.len() as u32 {
-            log::warn!(
-                "Marker count {} does not match length of marker vec {}",
-                item.marker_count,
-                item.positions.len()
-            );
-            dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
-        } else {
-            dst.extend_from_slice(&(item.positions.len() as u32).to_le_bytes()[..]);
+// Rigid body, skeleton, and asset ids are assigned independently, so the same number can name
+// different things; tagging each id with its kind keeps them apart in id-keyed maps
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StreamingId {
+    RigidBody(u32),
+    Skeleton(u32),
+    Asset(u32),
+}
+
+impl StreamingId {
+    pub fn raw(&self) -> u32 {
+        match *self {
+            StreamingId::RigidBody(id) | StreamingId::Skeleton(id) | StreamingId::Asset(id) => id,
         }
-        item.positions.iter().for_each(|p| {
-            dst.extend_from_slice(&p.x.to_le_bytes()[..]);
-            dst.extend_from_slice(&p.y.to_le_bytes()[..]);
-            dst.extend_from_slice(&p.z.to_le_bytes()[..]);
-        });
-        Ok(())
     }
 }
 
-impl Decoder for MarkerSetCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = MarkerSet;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let mut name_buf = Vec::new();
-        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-        let name = String::from_utf8(name_buf)?;
+// 64 bit FNV-1a, which unlike `DefaultHasher` is the same across Rust releases and platforms
+struct ContentDigest(u64);
 
-        if src.remaining() < 16 {
-            return Err("Not enough bytest to decode MarkerSet".into());
-        }
-        log::debug!("MarkerSet name: '{}'", name);
+impl Default for ContentDigest {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
 
-        let marker_count = src.get_u32_le();
-        log::debug!("Marker count: {}", marker_count);
-        let positions = (0..marker_count)
-            .map(|_| Vec3 {
-                x: src.get_f32_le(),
-                y: src.get_f32_le(),
-                z: src.get_f32_le(),
-            })
-            .collect();
+impl ContentDigest {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
 
-        Ok(Self::Item {
-            name,
-            marker_count,
-            positions,
-        })
+    fn write_f32s(&mut self, values: &[f32]) {
+        values.iter().for_each(|v| self.write(&v.to_le_bytes()));
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct MarkerSet {
-    pub name: String,
-    pub marker_count: u32,
-    pub positions: Vec<Vec3>,
+/* FrameRing */
+
+// Keeps the latest `capacity` frames, dropping the oldest when a slow consumer falls behind
+#[derive(Debug)]
+pub struct FrameRing {
+    capacity: usize,
+    frames: VecDeque<FrameData>,
+    dropped: usize,
 }
 
-impl MarkerSet {
-    pub fn new(name: &str, marker_count: u32) -> Self {
+impl FrameRing {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "FrameRing capacity must be non-zero");
         Self {
-            name: name.to_string(),
-            marker_count,
-            positions: Vec::new(),
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+            dropped: 0,
         }
     }
-}
 
-/* RigidBody */
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
 
-#[derive(Debug, Default)]
-pub struct RigidBodyCodec {}
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
 
-impl Encoder<RigidBody> for RigidBodyCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: RigidBody, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the id, pos, and rot
-        dst.reserve(38);
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.rot.x.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.rot.y.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.rot.z.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.rot.w.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.mean_marker_err.to_le_bytes()[..]);
-        Ok(())
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
     }
-}
 
-impl Decoder for RigidBodyCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = RigidBody;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 32 {
-            return Err("Not enough bytes to decode RigidBody".into());
-        }
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
 
-        let id = src.get_u32_le();
-        let pos = Vec3 {
-            x: src.get_f32_le(),
-            y: src.get_f32_le(),
-            z: src.get_f32_le(),
-        };
-        let rot = Quat::from_xyzw(
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
-        )
-        .normalize();
+    pub fn push(&mut self, frame: FrameData) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+            self.dropped += 1;
+        }
+        self.frames.push_back(frame);
+    }
 
-        let mean_marker_err = src.get_f32_le();
-        let is_tracking_valid = (src.get_u16_le() & 0x01) != 0;
+    pub fn latest(&self) -> Option<&FrameData> {
+        self.frames.back()
+    }
 
-        Ok(RigidBody {
-            id,
-            pos,
-            rot,
-            is_tracking_valid,
-            mean_marker_err,
-        })
+    pub fn drain(&mut self) -> std::collections::vec_deque::Drain<'_, FrameData> {
+        self.frames.drain(..)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RigidBody {
-    pub id: u32,
-    pub pos: Vec3,
-    pub rot: Quat,
-    pub is_tracking_valid: bool,
-    pub mean_marker_err: f32,
+/* FrameDedup */
+
+// Drops frames whose number was already seen among the last `window` frame numbers. Frame
+// numbers are compared by wrapping distance, so numbering that wraps past u32::MAX still counts
+// as newer.  A frame `window` or more behind the newest is taken as the server restarting its
+// frame numbers (e.g. a playback loop), so the history is cleared and the frame accepted.
+#[derive(Debug)]
+pub struct FrameDedup {
+    window: u32,
+    newest: Option<u32>,
+    seen: VecDeque<u32>,
 }
 
-impl RigidBody {
-    pub fn rub_to_frd(self) -> Self {
+impl FrameDedup {
+    pub fn new(window: u32) -> Self {
+        assert!(window > 0, "FrameDedup window must be non-zero");
         Self {
-            pos: glam::vec3(self.pos.x, self.pos.z, -self.pos.y),
-            ..self
+            window,
+            newest: None,
+            seen: VecDeque::new(),
         }
     }
-}
-
-/* RigidBodyAsset */
-
-#[derive(Debug, Default)]
-pub struct RigidBodyAssetCodec {}
-
-impl Encoder<RigidBodyAsset> for RigidBodyAssetCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: RigidBodyAsset, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // Reserve enough space for at least the id, pos, rot, marker error, and param
-        dst.reserve(38);
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&bincode::serialize(&item.pos)?);
-        dst.extend_from_slice(&bincode::serialize(&item.rot)?);
-        dst.extend_from_slice(&item.marker_error.to_le_bytes()[..]);
-        dst.extend_from_slice(&(item.param).to_le_bytes()[..]);
 
-        Ok(())
+    pub fn window(&self) -> u32 {
+        self.window
     }
-}
 
-impl Decoder for RigidBodyAssetCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = RigidBodyAsset;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 38 {
-            return Err("Not enough bytes to decode RigidBodyAsset".into());
+    // Returns true the first time `frame_id` is seen
+    pub fn check(&mut self, frame_id: u32) -> bool {
+        if let Some(newest) = self.newest {
+            let behind = newest.wrapping_sub(frame_id);
+            if behind < u32::MAX / 2 {
+                if behind >= self.window {
+                    log::debug!("Frame {} is {} behind, restarting dedup", frame_id, behind);
+                    self.seen.clear();
+                    self.newest = Some(frame_id);
+                } else if self.seen.contains(&frame_id) {
+                    return false;
+                }
+            } else {
+                self.newest = Some(frame_id);
+            }
+        } else {
+            self.newest = Some(frame_id);
         }
+        self.seen.push_back(frame_id);
+        let newest = self.newest.unwrap_or(frame_id);
+        self.seen
+            .retain(|&seen| newest.wrapping_sub(seen) < self.window);
+        true
+    }
 
-        let id = src.get_u32_le();
-        let pos = Vec3 {
-            x: src.get_f32_le(),
-            y: src.get_f32_le(),
-            z: src.get_f32_le(),
-        };
-        let rot = Quat::from_xyzw(
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
-            src.get_f32_le(),
-        )
-        .normalize();
-
-        let marker_error = src.get_f32_le();
-        let param = src.get_i16_le();
-        Ok(RigidBodyAsset {
-            id,
-            pos,
-            rot,
-            marker_error,
-            param,
-        })
+    pub fn accept(&mut self, frame: &FrameData) -> bool {
+        self.check(frame.frame_id())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RigidBodyAsset {
-    pub id: u32,
-    pub pos: Vec3,
-    pub rot: Quat,
-    pub marker_error: f32,
-    pub param: i16,
+/* ReorderBuffer */
+
+// Releases frames in frame number order, holding early frames until the missing ones arrive.  A
+// missing frame is given up on once a frame `window` or more past it arrives, and frames that
+// arrive after their turn (or twice) are dropped.  A frame more than `window` behind is taken as
+// the server restarting its frame numbers: the held frames are released and ordering starts over
+// from it.  Frame numbers are compared by wrapping distance, as in `FrameDedup`.
+#[derive(Debug)]
+pub struct ReorderBuffer {
+    window: u32,
+    next: Option<u32>,
+    pending: VecDeque<FrameData>,
+    ready: VecDeque<FrameData>,
+    dropped: usize,
 }
 
-/* Skeleton */
+impl ReorderBuffer {
+    pub fn new(window: u32) -> Self {
+        assert!(window > 0, "ReorderBuffer window must be non-zero");
+        Self {
+            window,
+            next: None,
+            pending: VecDeque::new(),
+            ready: VecDeque::new(),
+            dropped: 0,
+        }
+    }
 
-#[derive(Debug, Default)]
-pub struct SkeletonCodec {}
+    pub fn window(&self) -> u32 {
+        self.window
+    }
 
-impl Encoder<Skeleton> for SkeletonCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Skeleton, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the id and rigidbody count
-        dst.reserve(8);
-        if item.rigid_body_count != item.rigid_bodies.len() as u32 {
-            log::warn!(
-                "RigidBody count {} does not match length of rigid_bodies vec {}",
-                item.rigid_body_count,
-                item.rigid_bodies.len()
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn push(&mut self, frame: FrameData) {
+        let mut next = *self.next.get_or_insert(frame.frame_number);
+        let mut ahead = frame.frame_number.wrapping_sub(next);
+        if ahead >= u32::MAX / 2 && next.wrapping_sub(frame.frame_number) > self.window {
+            log::debug!(
+                "Frame {} is {} behind, restarting frame order",
+                frame.frame_number,
+                next.wrapping_sub(frame.frame_number)
             );
-            dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
-        } else {
-            dst.extend_from_slice(&(item.rigid_bodies.len() as u32).to_le_bytes()[..]);
+            self.flush();
+            next = frame.frame_number;
+            self.next = Some(next);
+            ahead = 0;
         }
-        let mut rigid_body_codec = RigidBodyCodec::default();
-        for rb in item.rigid_bodies.into_iter() {
-            rigid_body_codec.encode(rb, dst)?;
+        if ahead >= u32::MAX / 2
+            || self
+                .pending
+                .iter()
+                .any(|held| held.frame_number == frame.frame_number)
+        {
+            self.dropped += 1;
+            return;
         }
-        Ok(())
+        let at = self
+            .pending
+            .partition_point(|held| held.frame_number.wrapping_sub(next) < ahead);
+        self.pending.insert(at, frame);
+        self.release();
     }
-}
 
-impl Decoder for SkeletonCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = Skeleton;
+    // The next frame in order, if one is ready
+    pub fn pop(&mut self) -> Option<FrameData> {
+        self.ready.pop_front()
+    }
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 8 {
-            return Err("Not enough bytes to decode Skeleton".into());
+    // Releases every held frame, e.g. at the end of a stream, skipping over any gaps
+    pub fn flush(&mut self) {
+        while let Some(frame) = self.pending.pop_front() {
+            self.next = Some(frame.frame_number.wrapping_add(1));
+            self.ready.push_back(frame);
+        }
+    }
+
+    fn release(&mut self) {
+        while let (Some(next), Some(first), Some(newest)) =
+            (self.next, self.pending.front(), self.pending.back())
+        {
+            if first.frame_number != next && newest.frame_number.wrapping_sub(next) < self.window {
+                break;
+            }
+            let frame = self.pending.pop_front().unwrap();
+            self.next = Some(frame.frame_number.wrapping_add(1));
+            self.ready.push_back(frame);
         }
-        let id = src.get_u32_le();
-        log::debug!("Skeleton ID: {}", id);
-        let rigid_body_count = src.get_u32_le();
-        log::debug!("Skeleton RigidBody Count: {}", rigid_body_count);
-        let mut rigidbody_codec = RigidBodyCodec::default();
-        let rigid_bodies: Vec<RigidBody> = (0..rigid_body_count)
-            .map(|_| rigidbody_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        log::debug!("Skeleton RigidBodies: {:?}", rigid_bodies);
-        Ok(Skeleton {
-            id,
-            rigid_body_count,
-            rigid_bodies,
-        })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Skeleton {
-    pub id: u32,
-    pub rigid_body_count: u32,
-    pub rigid_bodies: Vec<RigidBody>,
+/* MarkerSwapDetector */
+
+// Flags labeled markers that likely traded ids between consecutive frames.  A pair is flagged
+// when the total distance moved with their ids swapped is less than `ratio` times the distance
+// moved as labeled, i.e. the swap explains the motion much better.  Occluded markers are ignored.
+#[derive(Debug)]
+pub struct MarkerSwapDetector {
+    ratio: f32,
+    previous: HashMap<u32, Vec3>,
 }
 
-#[derive(Debug, Default)]
-pub struct AssetCodec {}
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MarkerSwap {
+    // labeled marker ids, with `first < second`
+    pub first: u32,
+    pub second: u32,
+}
 
-impl Encoder<Asset> for AssetCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Asset, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the id and rigidbody count
-        dst.reserve(8);
-        if item.rigid_body_count != item.rigid_bodies.len() as u32 {
-            log::warn!(
-                "RigidBody count {} does not match length of rigid_bodies vec {}",
-                item.rigid_body_count,
-                item.rigid_bodies.len()
-            );
-            dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
-        } else {
-            dst.extend_from_slice(&(item.rigid_bodies.len() as u32).to_le_bytes()[..]);
+impl MarkerSwapDetector {
+    pub fn new(ratio: f32) -> Self {
+        assert!(
+            ratio > 0.0 && ratio <= 1.0,
+            "MarkerSwapDetector ratio must be in (0, 1]"
+        );
+        Self {
+            ratio,
+            previous: HashMap::new(),
         }
-        let mut rigid_body_codec = RigidBodyAssetCodec::default();
-        for rb in item.rigid_bodies.into_iter() {
-            rigid_body_codec.encode(rb, dst)?;
+    }
+
+    // Compares `frame` with the previously fed frame, then remembers it for the next call
+    pub fn feed(&mut self, frame: &FrameData) -> Vec<MarkerSwap> {
+        let current: Vec<(u32, Vec3)> = frame
+            .labeled_marker_positions
+            .iter()
+            .filter(|marker| !marker.is_occluded())
+            .map(|marker| (marker.id, marker.pos))
+            .collect();
+        let tracked: Vec<(u32, Vec3, Vec3)> = current
+            .iter()
+            .filter_map(|&(id, pos)| self.previous.get(&id).map(|&prev| (id, prev, pos)))
+            .collect();
+        let mut swaps = Vec::new();
+        for (i, &(a, a_prev, a_pos)) in tracked.iter().enumerate() {
+            for &(b, b_prev, b_pos) in &tracked[i + 1..] {
+                let as_labeled = a_pos.distance(a_prev) + b_pos.distance(b_prev);
+                let swapped = a_pos.distance(b_prev) + b_pos.distance(a_prev);
+                if swapped < self.ratio * as_labeled {
+                    swaps.push(MarkerSwap {
+                        first: a.min(b),
+                        second: a.max(b),
+                    });
+                }
+            }
         }
-        Ok(())
+        self.previous.clear();
+        self.previous.extend(current);
+        swaps
     }
 }
 
-impl Decoder for AssetCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = Asset;
+impl Default for MarkerSwapDetector {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 8 {
-            return Err("Not enough bytes to decode Asset".into());
+/* LazyFrame */
+
+// Decodes a frame's header and section boundaries up front and each section's elements only when
+// first accessed, caching the result.  Relies on the section byte counts of NatNet 4.1+.
+#[derive(Debug)]
+pub struct LazyFrame {
+    raw: Bytes,
+    frame_number: u32,
+    // markersets, unlabeled markers, rigid bodies, skeletons, assets, labeled markers, force
+    // plates, and devices, in wire order
+    sections: [LazySection; 8],
+    markersets: OnceLock<Vec<MarkerSet>>,
+    rigid_bodies: OnceLock<Vec<RigidBody>>,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct LazySection {
+    count: u32,
+    start: usize,
+    len: usize,
+}
+
+impl LazyFrame {
+    // `src` starts at the packet size, as passed to `FrameDataCodec::decode`
+    pub fn new(src: BytesMut) -> Result<Self, DecodeError> {
+        let raw = src.freeze();
+        let mut buf = &raw[..];
+        if buf.len() < 6 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "FrameData",
+                expected: 6,
+                got: buf.len(),
+            });
         }
-        let id = src.get_u32_le();
-        let rigid_body_count = src.get_u32_le();
-        let mut rigidbody_codec = RigidBodyAssetCodec::default();
-        let rigid_bodies: Vec<RigidBodyAsset> = (0..rigid_body_count)
-            .map(|_| rigidbody_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(Asset {
-            id,
-            rigid_body_count,
-            rigid_bodies,
+        buf.advance(2);
+        let frame_number = buf.get_u32_le();
+        let mut sections = [LazySection::default(); 8];
+        for section in sections.iter_mut() {
+            if buf.len() < 8 {
+                return Err(DecodeError::NotEnoughBytes {
+                    item: "FrameData section",
+                    expected: 8,
+                    got: buf.len(),
+                });
+            }
+            let count = buf.get_u32_le();
+            let len = buf.get_u32_le() as usize;
+            if buf.len() < len {
+                return Err(DecodeError::NotEnoughBytes {
+                    item: "FrameData section",
+                    expected: len,
+                    got: buf.len(),
+                });
+            }
+            *section = LazySection {
+                count,
+                start: raw.len() - buf.len(),
+                len,
+            };
+            buf.advance(len);
+        }
+        Ok(Self {
+            raw,
+            frame_number,
+            sections,
+            markersets: OnceLock::new(),
+            rigid_bodies: OnceLock::new(),
         })
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Asset {
-    pub id: u32,
-    pub rigid_body_count: u32,
-    pub rigid_bodies: Vec<RigidBodyAsset>,
-}
+    pub fn frame_number(&self) -> u32 {
+        self.frame_number
+    }
 
-/* LabeledMarker */
+    pub fn markerset_count(&self) -> u32 {
+        self.sections[0].count
+    }
 
-#[derive(Debug, Default)]
-pub struct LabeledMarkerCodec {}
+    pub fn rigid_body_count(&self) -> u32 {
+        self.sections[2].count
+    }
 
-impl Encoder<LabeledMarker> for LabeledMarkerCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: LabeledMarker, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for entire struct
-        dst.reserve(26);
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.size.to_le_bytes()[..]);
-        match item.status {
-            LabeledMarkerStatus::Occluded => dst.extend_from_slice(&1_u16.to_le_bytes()[..]),
-            LabeledMarkerStatus::PointCloudSolved => {
-                dst.extend_from_slice(&2_u16.to_le_bytes()[..])
-            }
-            LabeledMarkerStatus::ModelSolved => dst.extend_from_slice(&4_u16.to_le_bytes()[..]),
-            LabeledMarkerStatus::Unrecognized => dst.extend_from_slice(&0_u16.to_le_bytes()[..]),
-        };
-        Ok(())
+    fn section(&self, index: usize) -> &[u8] {
+        let section = self.sections[index];
+        &self.raw[section.start..section.start + section.len]
     }
-}
 
-impl Decoder for LabeledMarkerCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = LabeledMarker;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 26 {
-            return Err("Not enough bytes to decode LabeledMarker".into());
+    pub fn markersets(&self) -> Result<&[MarkerSet], DecodeError> {
+        if let Some(markersets) = self.markersets.get() {
+            return Ok(markersets);
         }
-        let id = src.get_u32_le();
-        let pos = Vec3 {
-            x: src.get_f32_le(),
-            y: src.get_f32_le(),
-            z: src.get_f32_le(),
-        };
-        let size = src.get_f32_le();
-        let status = match src.get_u16_le() {
-            0x01 => LabeledMarkerStatus::Occluded,
-            0x02 => LabeledMarkerStatus::PointCloudSolved,
-            0x04 => LabeledMarkerStatus::ModelSolved,
-            _ => LabeledMarkerStatus::Unrecognized,
-        };
-        let residual = src.get_f32_le();
-        Ok(LabeledMarker {
-            id,
-            pos,
-            size,
-            status,
-            residual,
-        })
+        let mut src = BytesMut::from(self.section(0));
+        let mut codec = MarkerSetCodec::default();
+        let markersets = (0..self.markerset_count())
+            .map(|_| codec.decode(&mut src))
+            .collect::<Result<_, _>>()?;
+        Ok(self.markersets.get_or_init(|| markersets))
+    }
+
+    pub fn rigid_bodies(&self) -> Result<&[RigidBody], DecodeError> {
+        if let Some(rigid_bodies) = self.rigid_bodies.get() {
+            return Ok(rigid_bodies);
+        }
+        let mut src = self.section(2);
+        let codec = RigidBodyCodec::default();
+        let rigid_bodies = (0..self.rigid_body_count())
+            .map(|_| codec.decode_buf(&mut src))
+            .collect::<Result<_, _>>()?;
+        Ok(self.rigid_bodies.get_or_init(|| rigid_bodies))
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct LabeledMarker {
-    pub id: u32,
-    pub pos: Vec3,
-    pub size: f32,
-    pub status: LabeledMarkerStatus,
-    pub residual: f32,
+// Model definition elements are prefixed with their byte size from NatNet 4.1, alongside the
+// FrameData section byte counts
+const MODELDEF_ELEMENT_SIZE_VERSION: NatNetVersion = NatNetVersion::new(4, 1, 0, 0);
+
+#[derive(Debug)]
+pub struct ModelDefCodec {
+    pub version: NatNetVersion,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum LabeledMarkerStatus {
-    Occluded,
-    PointCloudSolved,
-    ModelSolved,
-    Unrecognized,
+impl ModelDefCodec {
+    pub fn new(version: NatNetVersion) -> Self {
+        Self { version }
+    }
+
+    fn has_element_sizes(&self) -> bool {
+        self.version >= MODELDEF_ELEMENT_SIZE_VERSION
+    }
+
+    fn start_element(&self, dst: &mut BytesMut) -> Option<usize> {
+        self.has_element_sizes().then(|| start_section(dst))
+    }
+
+    fn end_element(&self, dst: &mut BytesMut, element_start: Option<usize>) {
+        if let Some(element_start) = element_start {
+            end_section(dst, element_start);
+        }
+    }
 }
 
-#[derive(Debug, Default)]
-pub struct ForcePlateCodec {}
+impl Default for ModelDefCodec {
+    fn default() -> Self {
+        Self::new(VERSION.into())
+    }
+}
 
-impl Encoder<ForcePlate> for ForcePlateCodec {
+impl Encoder<ModelDef> for ModelDefCodec {
     type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: ForcePlate, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least id and channel count
-        dst.reserve(8);
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.channel_count.to_le_bytes()[..]);
-        let mut force_plate_channel_codec = ForcePlateChannelCodec::default();
-        for ch in item.channels.into_iter() {
-            force_plate_channel_codec.encode(ch, dst)?;
+    fn encode(&mut self, item: ModelDef, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least packet size and dataset count
+        dst.reserve(6);
+        dst.extend_from_slice(&item.packet_size.to_le_bytes()[..]);
+        dst.extend_from_slice(&(item.dataset.len() as u32).to_le_bytes()[..]);
+        for data in item.dataset.into_iter() {
+            // the element size is recomputed rather than trusting the decoded `size`, and left out
+            // entirely for versions without element sizes
+            match data {
+                ModelDefData::MarkerSetDesc { data, .. } => {
+                    dst.put_u32_le(0);
+                    let element_start = self.start_element(dst);
+                    MarkerSetDescCodec.encode(*data, dst)?;
+                    self.end_element(dst, element_start);
+                }
+                ModelDefData::RigidBodyDesc { data, .. } => {
+                    dst.put_u32_le(1);
+                    let element_start = self.start_element(dst);
+                    RigidBodyDescCodec::new(self.version).encode(*data, dst)?;
+                    self.end_element(dst, element_start);
+                }
+                ModelDefData::CameraDesc { data, .. } => {
+                    dst.put_u32_le(5);
+                    let element_start = self.start_element(dst);
+                    CameraDescCodec.encode(*data, dst)?;
+                    self.end_element(dst, element_start);
+                }
+                ModelDefData::AssetDesc { data, .. } => {
+                    dst.put_u32_le(7);
+                    let element_start = self.start_element(dst);
+                    AssetDescCodec.encode(*data, dst)?;
+                    self.end_element(dst, element_start);
+                }
+                val => return Err(format!("Unsupported ModelDef data: {:?}", val).into()),
+            }
         }
         Ok(())
     }
 }
 
-impl Decoder for ForcePlateCodec {
+impl Decoder for ModelDefCodec {
+    type Item = ModelDef;
     type Error = Box<dyn error::Error>;
-    type Item = ForcePlate;
+
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 8 {
-            return Err("Not enough bytes to decode ForcePlate".into());
+        // a model with no descriptions is only the packet size and a zero dataset count
+        if src.remaining() < 6 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "ModelDef header",
+                expected: 6,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let packet_size = src.get_u16_le();
+        log::debug!("Packet Size: {} bytes", packet_size);
+        let dataset_count = src.get_u32_le();
+        let mut dataset = Vec::new();
+        log::debug!("DataSet Count: {}", dataset_count);
+        for _ in 0..dataset_count {
+            // the data type, and the element size where the version has one
+            let expected = if self.has_element_sizes() { 8 } else { 4 };
+            if src.remaining() < expected {
+                return Err(DecodeError::NotEnoughBytes {
+                    item: "ModelDef data header",
+                    expected,
+                    got: src.remaining(),
+                }
+                .into());
+            }
+            let data_type = src.get_u32_le();
+            log::debug!("Data Type: {}", data_type);
+            // older versions have no size, so it's taken from what the element consumed
+            let size = if self.has_element_sizes() {
+                Some(src.get_u32_le())
+            } else {
+                None
+            };
+            log::debug!("Data Size: {:?}", size);
+            let element_start = src.remaining();
+            let data = match data_type {
+                0 => {
+                    let mut codec = MarkerSetDescCodec;
+                    let data = Box::new(codec.decode(src)?);
+                    ModelDefData::MarkerSetDesc {
+                        size: size.unwrap_or((element_start - src.remaining()) as u32),
+                        data,
+                    }
+                }
+                1 => {
+                    let mut codec = RigidBodyDescCodec::new(self.version);
+                    let data = Box::new(codec.decode(src)?);
+                    ModelDefData::RigidBodyDesc {
+                        size: size.unwrap_or((element_start - src.remaining()) as u32),
+                        data,
+                    }
+                }
+                5 => {
+                    // without a size only the name, position, and orientation are present
+                    let size = match size {
+                        Some(size) => size,
+                        None => {
+                            let name_len = src.iter().position(|&b| b == 0).ok_or(
+                                DecodeError::NotEnoughBytes {
+                                    item: "CameraDesc name",
+                                    expected: src.remaining() + 1,
+                                    got: src.remaining(),
+                                },
+                            )?;
+                            (name_len + 1 + 28) as u32
+                        }
+                    };
+                    if src.remaining() < size as usize {
+                        return Err(DecodeError::NotEnoughBytes {
+                            item: "CameraDesc",
+                            expected: size as usize,
+                            got: src.remaining(),
+                        }
+                        .into());
+                    }
+                    // bound the description so optional trailing fields can be detected
+                    let mut element = src.split_to(size as usize);
+                    let mut codec = CameraDescCodec;
+                    ModelDefData::CameraDesc {
+                        size,
+                        data: Box::new(codec.decode(&mut element)?),
+                    }
+                }
+                7 => {
+                    let Some(size) = size else {
+                        return Err(DecodeError::Invalid {
+                            reason: format!(
+                                "AssetDesc is not supported before NatNet {}",
+                                MODELDEF_ELEMENT_SIZE_VERSION
+                            ),
+                        }
+                        .into());
+                    };
+                    if src.remaining() < size as usize {
+                        return Err(DecodeError::NotEnoughBytes {
+                            item: "AssetDesc",
+                            expected: size as usize,
+                            got: src.remaining(),
+                        }
+                        .into());
+                    }
+                    let mut element = src.split_to(size as usize);
+                    let mut codec = AssetDescCodec;
+                    ModelDefData::AssetDesc {
+                        size,
+                        data: Box::new(codec.decode(&mut element)?),
+                    }
+                }
+                // skeleton, force plate, and device descriptions (and any newer type) aren't
+                // decoded, so they're skipped by their size
+                data_type => {
+                    let Some(size) = size else {
+                        return Err(DecodeError::Invalid {
+                            reason: format!(
+                                "ModelDef data type {} can't be skipped before NatNet {}",
+                                data_type, MODELDEF_ELEMENT_SIZE_VERSION
+                            ),
+                        }
+                        .into());
+                    };
+                    if src.remaining() < size as usize {
+                        return Err(DecodeError::NotEnoughBytes {
+                            item: "ModelDef data",
+                            expected: size as usize,
+                            got: src.remaining(),
+                        }
+                        .into());
+                    }
+                    log::debug!(
+                        "Skipping {} bytes of ModelDef data type {}",
+                        size,
+                        data_type
+                    );
+                    src.advance(size as usize);
+                    match data_type {
+                        2 => ModelDefData::SkeletonDesc,
+                        3 => ModelDefData::ForcePlateDesc,
+                        4 => ModelDefData::DeviceDesc,
+                        _ => ModelDefData::Unknown,
+                    }
+                }
+            };
+            dataset.push(data);
         }
 
-        let id = src.get_u32_le();
-        let channel_count = src.get_u32_le();
-        let mut force_plate_channel_codec = ForcePlateChannelCodec::default();
-        let channels = (0..channel_count)
-            .map(|_| force_plate_channel_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(ForcePlate {
-            id,
-            channel_count,
-            channels,
+        Ok(ModelDef {
+            packet_size,
+            dataset_count,
+            dataset,
         })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ForcePlate {
-    pub id: u32,
-    pub channel_count: u32,
-    pub channels: Vec<ForcePlateChannel>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDef {
+    pub packet_size: u16,
+    pub dataset_count: u32,
+    pub dataset: Vec<ModelDefData>,
 }
 
-#[derive(Debug, Default)]
-pub struct ForcePlateChannelCodec {}
+impl ModelDef {
+    pub fn to_packet(&self) -> Result<BytesMut, Box<dyn error::Error>> {
+        let mut dst = BytesMut::new();
+        dst.put_u16_le(MessageId::ModelDef.into());
+        let mut codec = ModelDefCodec::default();
+        codec.encode(self.clone(), &mut dst)?;
+        // packet size excludes the message id and the packet size field itself
+        let packet_size = u16::try_from(dst.len() - 4)?;
+        dst[2..4].copy_from_slice(&packet_size.to_le_bytes());
+        Ok(dst)
+    }
 
-impl Encoder<ForcePlateChannel> for ForcePlateChannelCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: ForcePlateChannel, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least value count and 1 value
-        dst.reserve(8);
-        dst.extend_from_slice(&item.value_count.to_le_bytes()[..]);
-        item.values
-            .into_iter()
-            .for_each(|v| dst.extend_from_slice(&v.to_le_bytes()[..]));
-        Ok(())
+    pub fn rigid_body_descs(&self) -> impl Iterator<Item = &RigidBodyDesc> {
+        self.dataset.iter().filter_map(|data| match data {
+            ModelDefData::RigidBodyDesc { data, .. } => Some(data.as_ref()),
+            _ => None,
+        })
     }
-}
 
-impl Decoder for ForcePlateChannelCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = ForcePlateChannel;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 4 {
-            return Err("Not enough bytes to decode ForcePlateChannel".into());
-        }
-        let value_count = src.get_u32_le();
-        let values = (0..value_count).map(|_| src.get_u32_le()).collect();
-        Ok(ForcePlateChannel {
-            value_count,
-            values,
+    pub fn marker_set_descs(&self) -> impl Iterator<Item = &MarkerSetDesc> {
+        self.dataset.iter().filter_map(|data| match data {
+            ModelDefData::MarkerSetDesc { data, .. } => Some(data.as_ref()),
+            _ => None,
         })
     }
-}
-#[derive(Debug, Clone)]
-pub struct ForcePlateChannel {
-    pub value_count: u32,
-    pub values: Vec<u32>,
-}
 
-#[derive(Debug, Default)]
-pub struct DeviceCodec {}
+    pub fn rigid_body_desc(&self, id: i32) -> Option<&RigidBodyDesc> {
+        self.rigid_body_descs().find(|desc| desc.id == id)
+    }
 
-impl Encoder<Device> for DeviceCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Device, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least id and channel count
-        dst.reserve(8);
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.channel_count.to_le_bytes()[..]);
-        let mut device_channel_codec = DeviceChannelCodec::default();
-        for ch in item.channels.into_iter() {
-            device_channel_codec.encode(ch, dst)?;
-        }
-        Ok(())
+    pub fn marker_set_desc(&self, name: &str) -> Option<&MarkerSetDesc> {
+        self.marker_set_descs().find(|desc| desc.name == name)
     }
-}
 
-impl Decoder for DeviceCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = Device;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        // must have at least an id and a channel count
-        if src.remaining() < 8 {
-            return Err("Not enough bytes to decode Device".into());
-        }
-        let id = src.get_u32_le();
-        let channel_count = src.get_u32_le();
-        let mut device_channel_codec = DeviceChannelCodec::default();
-        let channels = (0..channel_count)
-            .map(|_| device_channel_codec.decode(src))
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(Device {
-            id,
-            channel_count,
-            channels,
+    pub fn asset_descs(&self) -> impl Iterator<Item = &AssetDesc> {
+        self.dataset.iter().filter_map(|data| match data {
+            ModelDefData::AssetDesc { data, .. } => Some(data.as_ref()),
+            _ => None,
         })
     }
+
+    pub fn asset_desc(&self, id: i32) -> Option<&AssetDesc> {
+        self.asset_descs().find(|desc| desc.id == id)
+    }
+
+    // Reports what changed going from `self` to `other`.  Rigid bodies and assets are matched by
+    // id; markersets have no id, so they're matched by name and a removed/added pair with the
+    // same marker names is reported as a rename.
+    pub fn diff(&self, other: &ModelDef) -> ModelDefDiff {
+        let mut diff = ModelDefDiff::default();
+        for old in self.rigid_body_descs() {
+            match other.rigid_body_desc(old.id) {
+                None => diff.removed_rigid_bodies.push(old.id),
+                Some(new) if new.name != old.name => {
+                    diff.renamed_rigid_bodies
+                        .push((old.id, old.name.clone(), new.name.clone()));
+                }
+                Some(new) if new != old => diff.changed_rigid_bodies.push(old.id),
+                Some(_) => {}
+            }
+        }
+        diff.added_rigid_bodies = other
+            .rigid_body_descs()
+            .filter(|new| self.rigid_body_desc(new.id).is_none())
+            .map(|new| new.id)
+            .collect();
+
+        let mut removed = Vec::new();
+        for old in self.marker_set_descs() {
+            match other.marker_set_desc(&old.name) {
+                None => removed.push(old),
+                Some(new) if new != old => diff.changed_marker_sets.push(old.name.clone()),
+                Some(_) => {}
+            }
+        }
+        let mut added: Vec<_> = other
+            .marker_set_descs()
+            .filter(|new| self.marker_set_desc(&new.name).is_none())
+            .collect();
+        for old in removed {
+            match added
+                .iter()
+                .position(|new| new.marker_names == old.marker_names)
+            {
+                Some(i) => {
+                    let new = added.remove(i);
+                    diff.renamed_marker_sets
+                        .push((old.name.clone(), new.name.clone()));
+                }
+                None => diff.removed_marker_sets.push(old.name.clone()),
+            }
+        }
+        diff.added_marker_sets = added.into_iter().map(|new| new.name.clone()).collect();
+
+        for old in self.asset_descs() {
+            match other.asset_desc(old.id) {
+                None => diff.removed_assets.push(old.id),
+                Some(new) if new.name != old.name => {
+                    diff.renamed_assets
+                        .push((old.id, old.name.clone(), new.name.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added_assets = other
+            .asset_descs()
+            .filter(|new| self.asset_desc(new.id).is_none())
+            .map(|new| new.id)
+            .collect();
+        diff
+    }
 }
-#[derive(Debug, Clone)]
-pub struct Device {
-    pub id: u32,
-    pub channel_count: u32,
-    pub channels: Vec<DeviceChannel>,
+
+// Result of `ModelDef::diff`.  Renames are (id, old name, new name), or (old name, new name) for
+// markersets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelDefDiff {
+    pub added_rigid_bodies: Vec<i32>,
+    pub removed_rigid_bodies: Vec<i32>,
+    pub renamed_rigid_bodies: Vec<(i32, String, String)>,
+    // same id and name, but a different parent, offset, or markers
+    pub changed_rigid_bodies: Vec<i32>,
+    pub added_marker_sets: Vec<String>,
+    pub removed_marker_sets: Vec<String>,
+    pub renamed_marker_sets: Vec<(String, String)>,
+    pub changed_marker_sets: Vec<String>,
+    pub added_assets: Vec<i32>,
+    pub removed_assets: Vec<i32>,
+    pub renamed_assets: Vec<(i32, String, String)>,
 }
 
+impl ModelDefDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Assembles a `ModelDef` for fixtures.  Element sizes are left at 0 since the encoder recomputes
+// them.
 #[derive(Debug, Default)]
-pub struct DeviceChannelCodec {}
+pub struct ModelDefBuilder {
+    dataset: Vec<ModelDefData>,
+}
 
-impl Encoder<DeviceChannel> for DeviceChannelCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: DeviceChannel, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least value count and a single value
-        dst.reserve(8);
-        dst.extend_from_slice(&item.value_count.to_le_bytes()[..]);
-        item.values
-            .into_iter()
-            .for_each(|v| dst.extend_from_slice(&v.to_le_bytes()[..]));
-        Ok(())
+impl ModelDefBuilder {
+    pub fn new() -> Self {
+        Self::default()
     }
-}
 
-impl Decoder for DeviceChannelCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = DeviceChannel;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        // must have at least a count and a single value
-        if src.remaining() < 8 {
-            return Err("Not enough bytes to decode DeviceChannel".into());
+    pub fn add_markerset_desc(mut self, desc: MarkerSetDesc) -> Self {
+        self.dataset.push(ModelDefData::MarkerSetDesc {
+            size: 0,
+            data: Box::new(desc),
+        });
+        self
+    }
+
+    pub fn add_rigid_body_desc(mut self, desc: RigidBodyDesc) -> Self {
+        self.dataset.push(ModelDefData::RigidBodyDesc {
+            size: 0,
+            data: Box::new(desc),
+        });
+        self
+    }
+
+    pub fn add_camera_desc(mut self, desc: CameraDesc) -> Self {
+        self.dataset.push(ModelDefData::CameraDesc {
+            size: 0,
+            data: Box::new(desc),
+        });
+        self
+    }
+
+    pub fn build(self) -> ModelDef {
+        ModelDef {
+            packet_size: 0,
+            dataset_count: self.dataset.len() as u32,
+            dataset: self.dataset,
         }
-        let value_count = src.get_u32_le();
-        let values = (0..value_count).map(|_| src.get_u32_le()).collect();
-        Ok(DeviceChannel {
-            value_count,
-            values,
-        })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DeviceChannel {
-    pub value_count: u32,
-    pub values: Vec<u32>,
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelDefData {
+    MarkerSetDesc { size: u32, data: Box<MarkerSetDesc> },
+    RigidBodyDesc { size: u32, data: Box<RigidBodyDesc> },
+    SkeletonDesc,
+    ForcePlateDesc,
+    DeviceDesc,
+    CameraDesc { size: u32, data: Box<CameraDesc> },
+    AssetDesc { size: u32, data: Box<AssetDesc> },
+    Unknown,
 }
 
+/* StringInterner */
+
+// A shared handle to an interned name; clones and comparisons don't touch the string data
+pub type Symbol = Arc<str>;
+
+// Hands out one `Symbol` per distinct string, so names repeated across model definitions (or
+// frames) share a single allocation for as long as the interner lives
 #[derive(Debug, Default)]
-pub struct StampsCodec {}
+pub struct StringInterner {
+    symbols: HashSet<Symbol>,
+}
 
-impl Encoder<Stamps> for StampsCodec {
-    type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: Stamps, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for entire struct
-        dst.reserve(32);
-        dst.extend_from_slice(&item.timestamp.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timestamp_mid.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timestamp_recv.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timestamp_tx.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timestamp_precision.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.timestamp_precision_fraction.to_le_bytes()[..]);
-        Ok(())
+impl StringInterner {
+    pub fn new() -> Self {
+        Self::default()
     }
-}
 
-impl Decoder for StampsCodec {
-    type Error = Box<dyn error::Error>;
-    type Item = Stamps;
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 42 {
-            return Err("Not enough bytes to decode Stamps".into());
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return symbol.clone();
         }
-        let timestamp = src.get_f64_le();
-        log::debug!("Timestamp: {}", timestamp);
-        let timestamp_mid = src.get_i64_le();
-        log::debug!("Timestamp Mid: {}", timestamp_mid);
-        let timestamp_recv = src.get_i64_le();
-        log::debug!("Timestamp Recv: {}", timestamp_recv);
-        let timestamp_tx = src.get_i64_le();
-        log::debug!("Timestamp Tx: {}", timestamp_tx);
-        let timestamp_precision = src.get_i32_le();
-        log::debug!("Timestamp Precision: {}", timestamp_precision);
-        let timestamp_precision_fraction = src.get_i32_le();
-        log::debug!(
-            "Timestamp Precision Fraction: {}",
-            timestamp_precision_fraction
-        );
+        let symbol: Symbol = Arc::from(name);
+        self.symbols.insert(symbol.clone());
+        symbol
+    }
 
-        Ok(Stamps {
-            timestamp,
-            timestamp_mid,
-            timestamp_recv,
-            timestamp_tx,
-            timestamp_precision,
-            timestamp_precision_fraction,
-        })
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Stamps {
-    pub timestamp: f64,
-    pub timestamp_mid: i64,
-    pub timestamp_recv: i64,
-    pub timestamp_tx: i64,
-    pub timestamp_precision: i32,
-    pub timestamp_precision_fraction: i32,
+// The names of a model definition, as interned symbols
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InternedModelDef {
+    // markerset names with their marker names
+    pub marker_sets: Vec<(Symbol, Vec<Symbol>)>,
+    pub rigid_bodies: Vec<(i32, Symbol)>,
+    pub assets: Vec<(i32, Symbol)>,
 }
 
-impl Default for Stamps {
-    fn default() -> Self {
-        Self {
-            timestamp: 0.0,
-            timestamp_mid: 0,
-            timestamp_recv: 0,
-            timestamp_tx: 0,
-            timestamp_precision: 0,
-            timestamp_precision_fraction: 0,
+impl ModelDef {
+    pub fn intern_names(&self, interner: &mut StringInterner) -> InternedModelDef {
+        InternedModelDef {
+            marker_sets: self
+                .marker_set_descs()
+                .map(|desc| {
+                    let marker_names = desc
+                        .marker_names
+                        .iter()
+                        .map(|name| interner.intern(name))
+                        .collect();
+                    (interner.intern(&desc.name), marker_names)
+                })
+                .collect(),
+            rigid_bodies: self
+                .rigid_body_descs()
+                .map(|desc| (desc.id, interner.intern(&desc.name)))
+                .collect(),
+            assets: self
+                .asset_descs()
+                .map(|desc| (desc.id, interner.intern(&desc.name)))
+                .collect(),
+        }
+    }
+}
+
+// Reads a nul terminated name in place and interns it, so a name the interner already holds is
+// never copied
+fn take_symbol(
+    buf: &mut &[u8],
+    interner: &mut StringInterner,
+) -> Result<Symbol, Box<dyn error::Error>> {
+    let len = buf
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(DecodeError::NotEnoughBytes {
+            item: "name",
+            expected: buf.len() + 1,
+            got: buf.len(),
+        })?;
+    let symbol = interner.intern(std::str::from_utf8(&buf[..len])?);
+    buf.advance(len + 1);
+    Ok(symbol)
+}
+
+fn take_u32_for(buf: &mut &[u8], item: &'static str) -> Result<u32, DecodeError> {
+    take_u32(buf).ok_or(DecodeError::NotEnoughBytes {
+        item,
+        expected: 4,
+        got: buf.len(),
+    })
+}
+
+fn skip_bytes_for(buf: &mut &[u8], len: usize, item: &'static str) -> Result<(), DecodeError> {
+    skip_bytes(buf, len).ok_or(DecodeError::NotEnoughBytes {
+        item,
+        expected: len,
+        got: buf.len(),
+    })
+}
+
+fn skip_name(buf: &mut &[u8], item: &'static str) -> Result<(), DecodeError> {
+    let len = buf
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(DecodeError::NotEnoughBytes {
+            item,
+            expected: buf.len() + 1,
+            got: buf.len(),
+        })?;
+    buf.advance(len + 1);
+    Ok(())
+}
+
+impl ModelDefCodec {
+    // Decodes a model definition keeping only its names, interned.  Names are read in place and
+    // looked up before anything is allocated, and all other fields are skipped, so re-fetching
+    // an unchanged model definition allocates nothing but the returned vectors.
+    pub fn decode_interned(
+        &mut self,
+        src: &mut BytesMut,
+        interner: &mut StringInterner,
+    ) -> Result<InternedModelDef, Box<dyn error::Error>> {
+        if src.remaining() < 6 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "ModelDef header",
+                expected: 6,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let mut buf = &src[..];
+        buf.advance(2);
+        let dataset_count = buf.get_u32_le();
+        let rigid_body_codec = RigidBodyDescCodec::new(self.version);
+        let mut model = InternedModelDef::default();
+        for _ in 0..dataset_count {
+            let data_type = take_u32_for(&mut buf, "ModelDef data type")?;
+            let size = if self.has_element_sizes() {
+                Some(take_u32_for(&mut buf, "ModelDef data size")? as usize)
+            } else {
+                None
+            };
+            let element = buf;
+            match data_type {
+                0 => {
+                    let name = take_symbol(&mut buf, interner)?;
+                    let marker_count = take_u32_for(&mut buf, "MarkerSetDesc")?;
+                    let marker_names = (0..marker_count)
+                        .map(|_| take_symbol(&mut buf, interner))
+                        .collect::<Result<_, _>>()?;
+                    model.marker_sets.push((name, marker_names));
+                }
+                1 => {
+                    let name = take_symbol(&mut buf, interner)?;
+                    let id = take_u32_for(&mut buf, "RigidBodyDesc")? as i32;
+                    // parent id and offset
+                    skip_bytes_for(&mut buf, 16, "RigidBodyDesc")?;
+                    if rigid_body_codec.has_markers() {
+                        let marker_count = take_u32_for(&mut buf, "RigidBodyDesc")? as i32;
+                        let marker_count = marker_count.max(0) as usize;
+                        skip_bytes_for(&mut buf, marker_count * 16, "RigidBodyDesc markers")?;
+                        if rigid_body_codec.has_marker_names() {
+                            for _ in 0..marker_count {
+                                skip_name(&mut buf, "RigidBodyDesc marker name")?;
+                            }
+                        }
+                    }
+                    model.rigid_bodies.push((id, name));
+                }
+                5 => {
+                    // only skipped; without a size it's the name, position, and orientation
+                    if size.is_none() {
+                        skip_name(&mut buf, "CameraDesc name")?;
+                        skip_bytes_for(&mut buf, 28, "CameraDesc")?;
+                    }
+                }
+                7 => {
+                    if size.is_none() {
+                        return Err(DecodeError::Invalid {
+                            reason: format!(
+                                "AssetDesc is not supported before NatNet {}",
+                                MODELDEF_ELEMENT_SIZE_VERSION
+                            ),
+                        }
+                        .into());
+                    }
+                    let name = take_symbol(&mut buf, interner)?;
+                    // asset type
+                    skip_bytes_for(&mut buf, 4, "AssetDesc")?;
+                    let id = take_u32_for(&mut buf, "AssetDesc")? as i32;
+                    model.assets.push((id, name));
+                }
+                val => {
+                    return Err(DecodeError::Invalid {
+                        reason: format!("Unsupported ModelDef data type: {}", val),
+                    }
+                    .into());
+                }
+            }
+            // a sized element may hold fields that weren't read
+            if let Some(size) = size {
+                buf = element;
+                skip_bytes_for(&mut buf, size, "ModelDef data")?;
+            }
+        }
+        let consumed = src.len() - buf.len();
+        src.advance(consumed);
+        Ok(model)
+    }
+}
+
+impl FrameDataCodec {
+    // Interns the markerset names of the frame in `src` (starting at the packet size, as passed
+    // to `decode`) in place, without building a `FrameData` or copying known names
+    pub fn markerset_names_interned(
+        &self,
+        src: &[u8],
+        interner: &mut StringInterner,
+    ) -> Result<Vec<Symbol>, Box<dyn error::Error>> {
+        let mut buf = src;
+        // packet size and frame number
+        skip_bytes_for(&mut buf, 6, "FrameData")?;
+        let markerset_count = take_u32_for(&mut buf, "MarkerSet section")?;
+        if self.has_section_bytes() {
+            skip_bytes_for(&mut buf, 4, "MarkerSet section")?;
+        }
+        let mut names = Vec::with_capacity(markerset_count as usize);
+        for _ in 0..markerset_count {
+            names.push(take_symbol(&mut buf, interner)?);
+            let marker_count = take_u32_for(&mut buf, "MarkerSet")?;
+            skip_bytes_for(&mut buf, marker_count as usize * 12, "MarkerSet markers")?;
         }
+        Ok(names)
     }
 }
 
 #[derive(Debug, Default)]
-pub struct FrameParametersCodec {}
+pub struct Vec3Codec {
+    pub strict: bool,
+}
 
-impl Encoder<FrameParameters> for FrameParametersCodec {
+impl Encoder<Vec3> for Vec3Codec {
     type Error = Box<dyn error::Error>;
-    fn encode(&mut self, item: FrameParameters, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least value count and 1 value
-        dst.reserve(2);
-        dst.extend_from_slice(&item.param.to_le_bytes());
+    fn encode(&mut self, item: Vec3, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&bincode::serialize(&item)?);
         Ok(())
     }
 }
 
-impl Decoder for FrameParametersCodec {
+impl Decoder for Vec3Codec {
+    type Item = Vec3;
     type Error = Box<dyn error::Error>;
-    type Item = FrameParameters;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        if src.remaining() < 2 {
-            return Err("Not enough bytes to decode FrameParameters".into());
+        let vec = Vec3 {
+            x: src.get_f32_le(),
+            y: src.get_f32_le(),
+            z: src.get_f32_le(),
+        };
+        if self.strict {
+            ensure_finite("Vec3", vec.is_finite())?;
         }
-        let param = src.get_i16_le();
-        log::debug!("Param: {}", param);
-        let is_recording = (param & 0x01) != 0;
-        log::debug!("Is Recording: {}", is_recording);
-        let tracked_models_changed = (param & 0x02) != 0;
-        log::debug!("Tracking Models Changed: {}", tracked_models_changed);
-        Ok(FrameParameters {
-            param,
-            is_recording,
-            tracked_models_changed,
-        })
+        Ok(vec)
     }
 }
 
-#[derive(Debug, Copy, Clone, Default)]
-pub struct FrameParameters {
-    pub param: i16,
-    pub is_recording: bool,
-    pub tracked_models_changed: bool,
+#[derive(Debug, Default)]
+pub struct QuatCodec {
+    pub strict: bool,
 }
 
-/* MarkerSetDesc */
+impl Encoder<Quat> for QuatCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: Quat, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&bincode::serialize(&item)?);
+        Ok(())
+    }
+}
+
+impl Decoder for QuatCodec {
+    type Item = Quat;
+    type Error = Box<dyn error::Error>;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let quat = Quat::from_xyzw(
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+        );
+        Ok(normalize_rot("Quat", quat, self.strict)?)
+    }
+}
+
+/* Marker Asset */
 
+// Marker portion of an asset, following the asset's rigid bodies in the frame asset section
 #[derive(Debug, Default)]
-pub struct MarkerSetDescCodec;
+pub struct MarkerAssetCodec {
+    pub strict: bool,
+}
 
-impl Encoder<MarkerSetDesc> for MarkerSetDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    fn encode(&mut self, item: MarkerSetDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the name, marker count, and a single position
-        dst.reserve(item.name.len() + 16);
-        dst.extend_from_slice(item.name.as_bytes());
-        // end string with null terminator
-        dst.put_u8(0);
-        if item.marker_count != item.marker_names.len() as i32 {
+impl Encoder<MarkerAsset> for MarkerAssetCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: MarkerAsset, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // Reserve enough space for at least the marker count
+        dst.reserve(4);
+        if item.marker_count != item.marker_positions.len() as u32 {
             log::warn!(
-                "Marker count {} does not match length of marker vec {}",
+                "Marker count {} does not match length of marker_positions vec {}",
                 item.marker_count,
-                item.marker_names.len()
+                item.marker_positions.len()
             );
             dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
         } else {
-            dst.extend_from_slice(&(item.marker_names.len() as i32).to_le_bytes()[..]);
+            dst.extend_from_slice(&(item.marker_positions.len() as u32).to_le_bytes()[..]);
         }
-        item.marker_names.iter().for_each(|n| {
-            dst.extend_from_slice(n.as_bytes());
+        // markers without an id, size, params, or residual are written with zeros
+        item.marker_positions.iter().enumerate().for_each(|(i, p)| {
+            dst.put_u32_le(item.marker_ids.get(i).copied().unwrap_or(0));
+            dst.extend_from_slice(&p.x.to_le_bytes()[..]);
+            dst.extend_from_slice(&p.y.to_le_bytes()[..]);
+            dst.extend_from_slice(&p.z.to_le_bytes()[..]);
+            dst.put_f32_le(item.marker_sizes.get(i).copied().unwrap_or(0.0));
+            dst.put_i16_le(item.marker_params.get(i).copied().unwrap_or(0));
+            dst.put_f32_le(item.marker_residuals.get(i).copied().unwrap_or(0.0));
         });
         Ok(())
     }
 }
 
-impl Decoder for MarkerSetDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    type Item = MarkerSetDesc;
+impl Decoder for MarkerAssetCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = MarkerAsset;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let mut name_buf = Vec::new();
-        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-        let name = String::from_utf8(name_buf)?;
+        let mut markers = MarkerAsset::default();
+        self.decode_into(src, &mut markers)?;
+        Ok(markers)
+    }
+}
 
-        if src.remaining() < 16 {
-            let msg = "Not enough bytest to decode MarkerSetDesc";
-            log::error!("{}", msg);
-            return Err(msg.into());
+impl DecodeInto for MarkerAssetCodec {
+    fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        item: &mut MarkerAsset,
+    ) -> Result<(), Self::Error> {
+        if src.remaining() < 4 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "MarkerAsset",
+                expected: 4,
+                got: src.remaining(),
+            }
+            .into());
         }
-        log::debug!("MarkerSet name: '{}'", name);
-
-        let marker_count = src.get_i32_le();
-        log::debug!("Marker count: {}", marker_count);
-
-        let mut marker_names = Vec::new();
+        let marker_count = src.get_u32_le();
+        // id, position, size, params, and residual for each marker
+        let expected = marker_count as usize * 26;
+        if src.remaining() < expected {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "MarkerAsset markers",
+                expected,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        item.marker_count = marker_count;
+        item.marker_ids.clear();
+        item.marker_positions.clear();
+        item.marker_sizes.clear();
+        item.marker_params.clear();
+        item.marker_residuals.clear();
         for _ in 0..marker_count {
-            let mut name_buf = Vec::new();
-            let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-            marker_names.push(String::from_utf8(name_buf)?);
+            item.marker_ids.push(src.get_u32_le());
+            item.marker_positions.push(Vec3 {
+                x: src.get_f32_le(),
+                y: src.get_f32_le(),
+                z: src.get_f32_le(),
+            });
+            item.marker_sizes.push(src.get_f32_le());
+            item.marker_params.push(src.get_i16_le());
+            item.marker_residuals.push(src.get_f32_le());
         }
-
-        Ok(Self::Item {
-            name,
-            marker_count,
-            marker_names,
-        })
+        if self.strict {
+            ensure_finite(
+                "MarkerAsset.marker_positions",
+                item.marker_positions.iter().all(|p| p.is_finite()),
+            )?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct MarkerSetDesc {
-    pub name: String,
-    pub marker_count: i32,
-    pub marker_names: Vec<String>,
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarkerAsset {
+    pub marker_count: u32,
+    pub marker_ids: Vec<u32>,
+    pub marker_positions: Vec<Vec3>,
+    pub marker_sizes: Vec<f32>,
+    pub marker_params: Vec<i16>,
+    pub marker_residuals: Vec<f32>,
 }
 
-impl MarkerSetDesc {
-    pub fn new(name: &str, marker_count: i32) -> Self {
-        Self {
-            name: name.to_string(),
-            marker_count,
-            marker_names: Vec::new(),
-        }
+/* MarkerSet */
+
+const AGGREGATE_MARKERSET_NAME: &str = "all";
+
+// Positions closer than this to the origin are treated as occluded
+const OCCLUDED_EPSILON: f32 = 1e-6;
+
+fn centroid(positions: &[Vec3]) -> Option<Vec3> {
+    if positions.is_empty() {
+        return None;
     }
+    Some(positions.iter().sum::<Vec3>() / positions.len() as f32)
 }
 
-/* RigidBodyDesc */
-
 #[derive(Debug, Default)]
-pub struct RigidBodyDescCodec;
+pub struct MarkerSetCodec {
+    pub strict: bool,
+}
 
-impl Encoder<RigidBodyDesc> for RigidBodyDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    fn encode(&mut self, item: RigidBodyDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        // reserve enough space for at least the id, pos, and rot
-        //dst.reserve(38);
+impl Encoder<MarkerSet> for MarkerSetCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: MarkerSet, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the name, marker count, and a single position
+        dst.reserve(item.name.len() + 16);
         dst.extend_from_slice(item.name.as_bytes());
-        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.parent_id.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
-        dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
-        item.marker_offsets.iter().for_each(|m| {
-            dst.extend_from_slice(&m.x.to_le_bytes()[..]);
-            dst.extend_from_slice(&m.x.to_le_bytes()[..]);
-            dst.extend_from_slice(&m.x.to_le_bytes()[..]);
-        });
-        item.marker_active_labels.iter().for_each(|m| {
-            dst.extend_from_slice(&m.to_le_bytes()[..]);
-            dst.extend_from_slice(&m.to_le_bytes()[..]);
-            dst.extend_from_slice(&m.to_le_bytes()[..]);
-        });
-        item.marker_names.iter().for_each(|m| {
-            dst.extend_from_slice(m.as_bytes());
-            dst.extend_from_slice(m.as_bytes());
-            dst.extend_from_slice(m.as_bytes());
+        // end string with null terminator
+        dst.put_u8(0);
+        if item.marker_count != item.positions.len() as u32 {
+            log::warn!(
+                "Marker count {} does not match length of marker vec {}",
+                item.marker_count,
+                item.positions.len()
+            );
+            dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
+        } else {
+            dst.extend_from_slice(&(item.positions.len() as u32).to_le_bytes()[..]);
+        }
+        item.positions.iter().for_each(|p| {
+            dst.extend_from_slice(&p.x.to_le_bytes()[..]);
+            dst.extend_from_slice(&p.y.to_le_bytes()[..]);
+            dst.extend_from_slice(&p.z.to_le_bytes()[..]);
         });
         Ok(())
     }
 }
 
-impl Decoder for RigidBodyDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    type Item = RigidBodyDesc;
+impl Decoder for MarkerSetCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = MarkerSet;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let mut name_buf = Vec::new();
+        let mut markerset = MarkerSet::new("", 0);
+        self.decode_into(src, &mut markerset)?;
+        Ok(markerset)
+    }
+}
+
+impl DecodeInto for MarkerSetCodec {
+    fn decode_into(&mut self, src: &mut BytesMut, item: &mut MarkerSet) -> Result<(), Self::Error> {
+        let mut name_buf = std::mem::take(&mut item.name).into_bytes();
+        name_buf.clear();
         let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-        let name = String::from_utf8(name_buf)?;
-        log::debug!("RigidBodyDesc name: '{}'", name);
+        // drop the null terminator so names round-trip through the encoder
+        if name_buf.last() == Some(&b'\0') {
+            name_buf.pop();
+        }
+        item.name = String::from_utf8(name_buf)?;
 
-        let id = src.get_i32_le();
-        let parent_id = src.get_i32_le();
+        // an empty markerset is only its marker count
+        if src.remaining() < 4 {
+            return Err("Not enough bytest to decode MarkerSet".into());
+        }
+        log::debug!("MarkerSet name: '{}'", item.name);
 
-        let pos = Vec3 {
+        let marker_count = src.get_u32_le();
+        log::debug!("Marker count: {}", marker_count);
+        let expected = marker_count as usize * 12;
+        if src.remaining() < expected {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "MarkerSet markers",
+                expected,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        item.marker_count = marker_count;
+        item.positions.clear();
+        item.positions.extend((0..marker_count).map(|_| Vec3 {
             x: src.get_f32_le(),
             y: src.get_f32_le(),
             z: src.get_f32_le(),
-        };
+        }));
+        if self.strict {
+            ensure_finite(
+                "MarkerSet.positions",
+                item.positions.iter().all(|p| p.is_finite()),
+            )?;
+        }
+        Ok(())
+    }
+}
 
-        let marker_count = src.get_i32_le();
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkerSet {
+    pub name: String,
+    pub marker_count: u32,
+    pub positions: Vec<Vec3>,
+}
 
-        let marker_offsets = (0..marker_count)
-            .map(|_| Vec3 {
-                x: src.get_f32_le(),
-                y: src.get_f32_le(),
-                z: src.get_f32_le(),
-            })
-            .collect();
+impl MarkerSet {
+    pub fn new(name: &str, marker_count: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            marker_count,
+            positions: Vec::new(),
+        }
+    }
 
-        let marker_active_labels = (0..marker_count).map(|_| src.get_i32_le()).collect();
+    pub fn centroid(&self) -> Option<Vec3> {
+        centroid(&self.positions)
+    }
 
-        let mut marker_names = Vec::new();
-        for _ in 0..marker_count {
-            let mut name_buf = Vec::new();
-            let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-            marker_names.push(String::from_utf8(name_buf)?);
+    // Motive streams every reconstructed marker in a reserved markerset named "all"
+    pub fn is_aggregate(&self) -> bool {
+        self.name == AGGREGATE_MARKERSET_NAME
+    }
+
+    // Motive streams a labeled marker it couldn't see this frame at (0, 0, 0), so a position at
+    // the origin is counted as occluded.  A marker genuinely at the origin is indistinguishable.
+    pub fn occluded_count(&self) -> usize {
+        self.positions
+            .iter()
+            .filter(|pos| pos.abs_diff_eq(Vec3::ZERO, OCCLUDED_EPSILON))
+            .count()
+    }
+
+    // Fraction of positions that are occluded, 0.0 for an empty markerset
+    pub fn occlusion_rate(&self) -> f32 {
+        if self.positions.is_empty() {
+            return 0.0;
         }
+        self.occluded_count() as f32 / self.positions.len() as f32
+    }
 
-        Ok(RigidBodyDesc {
-            name,
-            id,
-            parent_id,
-            pos,
-            marker_count,
-            marker_offsets,
-            marker_active_labels,
-            marker_names,
+    // Pairs each position with its name from the matching `MarkerSetDesc`.  Positions without a
+    // name (i.e. the counts disagree) are named by their index.
+    pub fn labeled_positions<'a>(
+        &'a self,
+        desc: &'a MarkerSetDesc,
+    ) -> impl Iterator<Item = (Cow<'a, str>, Vec3)> {
+        self.positions.iter().enumerate().map(|(i, pos)| {
+            let name = match desc.marker_names.get(i) {
+                Some(name) => Cow::Borrowed(name.as_str()),
+                None => Cow::Owned(i.to_string()),
+            };
+            (name, *pos)
         })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RigidBodyDesc {
-    pub name: String,
-    pub id: i32,
-    pub parent_id: i32,
-    pub pos: Vec3,
-    pub marker_count: i32,
-    pub marker_offsets: Vec<Vec3>,
-    pub marker_active_labels: Vec<i32>,
-    pub marker_names: Vec<String>,
-}
-
-/* CameraDesc */
+/* RigidBody */
 
 #[derive(Debug, Default)]
-pub struct CameraDescCodec;
+pub struct RigidBodyCodec {
+    pub strict: bool,
+}
 
-impl Encoder<CameraDesc> for CameraDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    fn encode(&mut self, item: CameraDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
+impl Encoder<RigidBody> for RigidBodyCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: RigidBody, dst: &mut BytesMut) -> Result<(), Self::Error> {
         // reserve enough space for at least the id, pos, and rot
-        dst.reserve(item.name.len() + 28);
-        dst.extend_from_slice(item.name.as_bytes());
+        dst.reserve(38);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
         dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
         dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
         dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
@@ -1516,100 +3633,5535 @@ impl Encoder<CameraDesc> for CameraDescCodec {
         dst.extend_from_slice(&item.rot.y.to_le_bytes()[..]);
         dst.extend_from_slice(&item.rot.z.to_le_bytes()[..]);
         dst.extend_from_slice(&item.rot.w.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.mean_marker_err.to_le_bytes()[..]);
+        dst.extend_from_slice(&(item.is_tracking_valid as u16).to_le_bytes()[..]);
         Ok(())
     }
 }
 
-impl Decoder for CameraDescCodec {
-    type Error = Box<dyn std::error::Error>;
-    type Item = CameraDesc;
+impl Decoder for RigidBodyCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = RigidBody;
     fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
-        let mut name_buf = Vec::new();
-        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
-        let name = String::from_utf8(name_buf)?;
-        log::debug!("CameraDesc name: {}", name);
+        self.decode_buf(src)
+    }
+}
+
+impl RigidBodyCodec {
+    // Shared with the borrowing visitors, which read from slices rather than a `BytesMut`
+    fn decode_buf<B: Buf>(&self, src: &mut B) -> Result<RigidBody, Box<dyn error::Error>> {
+        // id, pos, rot, mean marker error, and tracking flags
+        if src.remaining() < 38 {
+            return Err("Not enough bytes to decode RigidBody".into());
+        }
 
+        let id = src.get_u32_le();
         let pos = Vec3 {
             x: src.get_f32_le(),
             y: src.get_f32_le(),
             z: src.get_f32_le(),
         };
-        log::debug!("CameraDesc pos: {}", pos);
-
         let rot = Quat::from_xyzw(
             src.get_f32_le(),
             src.get_f32_le(),
             src.get_f32_le(),
             src.get_f32_le(),
         );
-        log::debug!("CameraDesc rot: {}", rot);
+        if self.strict {
+            ensure_finite("RigidBody.pos", pos.is_finite())?;
+        }
+        let rot = normalize_rot("RigidBody.rot", rot, self.strict)?;
 
-        Ok(CameraDesc { name, pos, rot })
+        let mean_marker_err = src.get_f32_le();
+        let is_tracking_valid = (src.get_u16_le() & 0x01) != 0;
+
+        Ok(RigidBody {
+            id,
+            pos,
+            rot,
+            is_tracking_valid,
+            mean_marker_err,
+        })
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CameraDesc {
-    pub name: String,
+impl DecodeInto for RigidBodyCodec {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RigidBody {
+    pub id: u32,
     pub pos: Vec3,
     pub rot: Quat,
+    pub is_tracking_valid: bool,
+    pub mean_marker_err: f32,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl RigidBody {
+    // Remaps the position only; `rot` is left in Motive's frame.  Use `convert_coords_in_place` to
+    // re-express the orientation as well.
+    pub fn rub_to_frd(self) -> Self {
+        Self {
+            pos: CoordConversion::RubToFrd.apply_vec3(self.pos),
+            ..self
+        }
+    }
 
-    fn init() {
-        let _ = env_logger::builder()
-            .filter_level(log::LevelFilter::Trace)
-            .is_test(true)
-            .try_init();
+    pub fn convert_coords_in_place(&mut self, conv: CoordConversion) {
+        self.pos = conv.apply_vec3(self.pos);
+        self.rot = conv.apply_quat(self.rot);
     }
 
-    #[test]
-    fn parse_frame() {
-        init();
-        let path = std::path::PathBuf::from("src/FrameData.bin");
-        let packet = std::fs::read(path).unwrap();
-        let message = Message::from_bytes(&packet).expect("Failed to decode message from bytes");
-        match message {
-            Message::FrameData(frame) => {
-                assert_eq!(frame.packet_size, 1990);
-                assert_eq!(frame.frame_number, 169383987);
-                assert_eq!(frame.markerset_count, 6);
-                assert_eq!(frame.markerset_bytes, 1678);
-                assert_eq!(frame.unlabeled_marker_count, 0);
-                assert_eq!(frame.unlabeled_marker_bytes, 0);
-                assert_eq!(frame.rigid_body_count, 5);
-                assert_eq!(frame.rigid_body_bytes, 190);
-                assert_eq!(frame.skeleton_count, 0);
-                assert_eq!(frame.skeleton_bytes, 0);
-                assert_eq!(frame.labeled_marker_count, 0);
-                assert_eq!(frame.labeled_marker_bytes, 0);
-                assert_eq!(frame.device_count, 0);
-                assert_eq!(frame.device_bytes, 0);
-                assert_eq!(frame.device_count, 0);
-                assert_eq!(frame.device_bytes, 0);
-                assert!((frame.stamps.timestamp - 1411533.23).abs() < 1e-2);
-                assert_eq!(frame.stamps.timestamp_mid, 25742528329784);
-                assert_eq!(frame.stamps.timestamp_recv, 25742528388661);
-                assert_eq!(frame.stamps.timestamp_tx, 25742528459914);
-                assert_eq!(frame.stamps.timestamp_precision, 0);
-                assert_eq!(frame.stamps.timestamp_precision_fraction, 0);
-                assert_eq!(frame.frame_parameters.param, 0);
-                assert!(!frame.frame_parameters.is_recording);
-                assert!(!frame.frame_parameters.tracked_models_changed);
-            }
-            val => panic!("Expected FrameData, got {:?}", val),
+    // Homogeneous transform as column-major `[column][row]` arrays, translation in column 3
+    pub fn to_matrix4(&self) -> [[f32; 4]; 4] {
+        glam::Mat4::from_rotation_translation(self.rot, self.pos).to_cols_array_2d()
+    }
+
+    // Translation and rotation of this body expressed in `other`'s frame
+    pub fn relative_to(&self, other: &RigidBody) -> (Vec3, Quat) {
+        let inv_rot = other.rot.inverse();
+        (inv_rot * (self.pos - other.pos), inv_rot * self.rot)
+    }
+
+    // Pose at `t` between this body (0) and `next` (1), lerping the position and slerping the
+    // rotation.  The result is only tracked if both ends are.  Panics if the ids differ.
+    pub fn interpolate(&self, next: &RigidBody, t: f32) -> RigidBody {
+        assert_eq!(
+            self.id, next.id,
+            "Interpolating between different rigid bodies"
+        );
+        RigidBody {
+            id: self.id,
+            pos: self.pos.lerp(next.pos, t),
+            rot: self.rot.slerp(next.rot, t),
+            is_tracking_valid: self.is_tracking_valid && next.is_tracking_valid,
+            mean_marker_err: self.mean_marker_err
+                + (next.mean_marker_err - self.mean_marker_err) * t,
+        }
+    }
+}
+
+/* CoordConversion */
+
+// Axis remappings between coordinate conventions.  Each is a proper rotation, so orientations are
+// re-expressed by remapping the quaternion's vector part the same way as positions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CoordConversion {
+    // (x, y, z) -> (x, z, -y)
+    RubToFrd,
+    // (x, y, z) -> (x, -z, y)
+    YUpToZUp,
+    Identity,
+}
+
+impl CoordConversion {
+    pub fn apply_vec3(self, v: Vec3) -> Vec3 {
+        match self {
+            CoordConversion::RubToFrd => glam::vec3(v.x, v.z, -v.y),
+            CoordConversion::YUpToZUp => glam::vec3(v.x, -v.z, v.y),
+            CoordConversion::Identity => v,
+        }
+    }
+
+    pub fn apply_quat(self, q: Quat) -> Quat {
+        let v = self.apply_vec3(q.xyz());
+        Quat::from_xyzw(v.x, v.y, v.z, q.w)
+    }
+}
+
+/* RosPose */
+
+// Field order of geometry_msgs/Pose, `position` as x, y, z and `orientation` as x, y, z, w
+#[cfg(feature = "ros")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RosPose {
+    pub position: [f64; 3],
+    pub orientation: [f64; 4],
+}
+
+#[cfg(feature = "ros")]
+impl RigidBody {
+    // Motive is Y-up while ROS world frames (REP 103) are ENU, so this rotates +90 degrees about
+    // X: (x, y, z) -> (x, -z, y).  East is Motive's +X, north its -Z and up its +Y.  The
+    // quaternion's vector part rotates the same way.  Body-frame (FLU) consumers need their own
+    // choice of forward axis on top of this.
+    pub fn to_ros_pose(&self) -> RosPose {
+        let pos = self.pos.as_dvec3();
+        let rot = self.rot.as_dquat();
+        RosPose {
+            position: [pos.x, -pos.z, pos.y],
+            orientation: [rot.x, -rot.z, rot.y, rot.w],
+        }
+    }
+}
+
+/* C3dWriter */
+
+// Which frame data becomes C3D points
+#[cfg(feature = "c3d")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum C3dPoints {
+    // one point per labeled marker id, labelled "<model id>_<marker id>"
+    #[default]
+    LabeledMarkers,
+    // one point per named markerset position, labelled "<markerset>_<index>" from 1
+    MarkerSets,
+}
+
+#[cfg(feature = "c3d")]
+const C3D_BLOCK_SIZE: usize = 512;
+// processor type 84 marks Intel (little-endian) byte order
+#[cfg(feature = "c3d")]
+const C3D_PROCESSOR_INTEL: u8 = 84;
+#[cfg(feature = "c3d")]
+const C3D_POINT_GROUP: i8 = 1;
+#[cfg(feature = "c3d")]
+const C3D_ANALOG_GROUP: i8 = 2;
+
+// Writes a recorded frame sequence as an Intel-order, floating point C3D file: a header block,
+// the parameter blocks, then an x, y, z, residual quadruple per point and frame.  Positions stay
+// in meters.  A point missing or occluded in a frame gets a residual of -1, which C3D readers
+// treat as invalid.
+#[cfg(feature = "c3d")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct C3dWriter {
+    pub frame_rate: f32,
+    pub points: C3dPoints,
+}
+
+#[cfg(feature = "c3d")]
+impl C3dWriter {
+    pub fn new(frame_rate: f32) -> Self {
+        Self {
+            frame_rate,
+            points: C3dPoints::default(),
+        }
+    }
+
+    pub fn write<W: io::Write>(
+        &self,
+        frames: &[FrameData],
+        mut dst: W,
+    ) -> Result<(), Box<dyn error::Error>> {
+        let (labels, rows) = match self.points {
+            C3dPoints::LabeledMarkers => c3d_labeled_marker_points(frames),
+            C3dPoints::MarkerSets => c3d_markerset_points(frames),
         };
+        let point_count =
+            u16::try_from(labels.len()).map_err(|_| EncodeError::Overflow("C3D point count"))?;
+        let frame_count =
+            u16::try_from(frames.len()).map_err(|_| EncodeError::Overflow("C3D frame count"))?;
+
+        // the data start block is itself a parameter, but a fixed size one, so the parameter
+        // section is laid out once to size it and again with the real value
+        let param_len = self.parameters(&labels, point_count, frame_count, 0)?.len();
+        let param_blocks = param_len.div_ceil(C3D_BLOCK_SIZE);
+        let data_start =
+            u16::try_from(2 + param_blocks).map_err(|_| EncodeError::Overflow("C3D data start"))?;
+        let mut params = self.parameters(&labels, point_count, frame_count, data_start)?;
+        params.resize(param_blocks * C3D_BLOCK_SIZE, 0);
+        params[2] = param_blocks as u8;
+
+        let mut header = vec![0u8; C3D_BLOCK_SIZE];
+        header[0] = 2;
+        header[1] = 0x50;
+        header[2..4].copy_from_slice(&point_count.to_le_bytes());
+        header[6..8].copy_from_slice(&1u16.to_le_bytes());
+        header[8..10].copy_from_slice(&frame_count.to_le_bytes());
+        // a negative scale factor marks floating point data
+        header[12..16].copy_from_slice(&(-1.0f32).to_le_bytes());
+        header[16..18].copy_from_slice(&data_start.to_le_bytes());
+        header[20..24].copy_from_slice(&self.frame_rate.to_le_bytes());
+
+        let mut data = Vec::with_capacity(rows.len() * labels.len() * 16);
+        for row in &rows {
+            for point in row {
+                let (pos, residual) = match point {
+                    Some(pos) => (*pos, 0.0f32),
+                    None => (Vec3::ZERO, -1.0),
+                };
+                for value in [pos.x, pos.y, pos.z, residual] {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        data.resize(data.len().div_ceil(C3D_BLOCK_SIZE) * C3D_BLOCK_SIZE, 0);
+
+        dst.write_all(&header)?;
+        dst.write_all(&params)?;
+        dst.write_all(&data)?;
+        Ok(())
     }
 
-    #[test]
-    fn parse_modeldef() {
-        init();
-        let path = std::path::PathBuf::from("src/ModelDef.bin");
-        let packet = std::fs::read(path).unwrap();
-        let message = Message::from_bytes(&packet);
+    fn parameters(
+        &self,
+        labels: &[String],
+        point_count: u16,
+        frame_count: u16,
+        data_start: u16,
+    ) -> Result<Vec<u8>, EncodeError> {
+        let label_len = labels.iter().map(String::len).max().unwrap_or(0).max(1);
+        let label_dims = [
+            u8::try_from(label_len).map_err(|_| EncodeError::Overflow("C3D label length"))?,
+            u8::try_from(labels.len()).map_err(|_| EncodeError::Overflow("C3D label count"))?,
+        ];
+        let mut label_data = Vec::with_capacity(label_len * labels.len());
+        for label in labels {
+            label_data.extend_from_slice(label.as_bytes());
+            label_data.resize(label_data.len() + label_len - label.len(), b' ');
+        }
+
+        let mut params = C3dParameters::default();
+        params.group(C3D_POINT_GROUP, "POINT");
+        params.parameter(C3D_POINT_GROUP, "USED", 2, &[], &point_count.to_le_bytes());
+        params.parameter(
+            C3D_POINT_GROUP,
+            "FRAMES",
+            2,
+            &[],
+            &frame_count.to_le_bytes(),
+        );
+        params.parameter(
+            C3D_POINT_GROUP,
+            "DATA_START",
+            2,
+            &[],
+            &data_start.to_le_bytes(),
+        );
+        params.parameter(C3D_POINT_GROUP, "SCALE", 4, &[], &(-1.0f32).to_le_bytes());
+        params.parameter(
+            C3D_POINT_GROUP,
+            "RATE",
+            4,
+            &[],
+            &self.frame_rate.to_le_bytes(),
+        );
+        params.parameter(C3D_POINT_GROUP, "UNITS", -1, &[1], b"m");
+        params.parameter(C3D_POINT_GROUP, "LABELS", -1, &label_dims, &label_data);
+        params.group(C3D_ANALOG_GROUP, "ANALOG");
+        params.parameter(C3D_ANALOG_GROUP, "USED", 2, &[], &0u16.to_le_bytes());
+        Ok(params.finish())
+    }
+}
+
+// Group and parameter records, each holding the offset from its own offset field to the next
+// record; the last record's offset is 0
+#[cfg(feature = "c3d")]
+#[derive(Default)]
+struct C3dParameters {
+    buf: Vec<u8>,
+    last_offset: Option<usize>,
+}
+
+#[cfg(feature = "c3d")]
+impl C3dParameters {
+    fn group(&mut self, id: i8, name: &str) {
+        self.buf.extend_from_slice(&[name.len() as u8, (-id) as u8]);
+        self.buf.extend_from_slice(name.as_bytes());
+        self.record_end(&[]);
+    }
+
+    fn parameter(&mut self, group: i8, name: &str, data_type: i8, dims: &[u8], data: &[u8]) {
+        self.buf.extend_from_slice(&[name.len() as u8, group as u8]);
+        self.buf.extend_from_slice(name.as_bytes());
+        let mut body = vec![data_type as u8, dims.len() as u8];
+        body.extend_from_slice(dims);
+        body.extend_from_slice(data);
+        self.record_end(&body);
+    }
+
+    // writes the offset, the record body and an empty description
+    fn record_end(&mut self, body: &[u8]) {
+        self.last_offset = Some(self.buf.len());
+        let offset = 2 + body.len() as i16 + 1;
+        self.buf.extend_from_slice(&offset.to_le_bytes());
+        self.buf.extend_from_slice(body);
+        self.buf.push(0);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut buf = vec![1, 0x50, 0, C3D_PROCESSOR_INTEL];
+        buf.extend_from_slice(&self.buf);
+        if let Some(offset) = self.last_offset {
+            buf[4 + offset..4 + offset + 2].copy_from_slice(&0i16.to_le_bytes());
+        }
+        buf
+    }
+}
+
+#[cfg(feature = "c3d")]
+type C3dRows = Vec<Vec<Option<Vec3>>>;
+
+#[cfg(feature = "c3d")]
+fn c3d_labeled_marker_points(frames: &[FrameData]) -> (Vec<String>, C3dRows) {
+    let mut ids: Vec<u32> = frames
+        .iter()
+        .flat_map(|frame| {
+            frame
+                .labeled_marker_positions
+                .iter()
+                .map(|marker| marker.id)
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    ids.sort_unstable();
+    let index: HashMap<u32, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let rows = frames
+        .iter()
+        .map(|frame| {
+            let mut row = vec![None; ids.len()];
+            for marker in &frame.labeled_marker_positions {
+                if !marker.is_occluded() {
+                    row[index[&marker.id]] = Some(marker.pos);
+                }
+            }
+            row
+        })
+        .collect();
+    let labels = ids
+        .iter()
+        .map(|id| format!("{}_{}", id >> 16, id & 0xffff))
+        .collect();
+    (labels, rows)
+}
+
+#[cfg(feature = "c3d")]
+fn c3d_markerset_points(frames: &[FrameData]) -> (Vec<String>, C3dRows) {
+    // points keep the order they first appear in
+    let mut labels = Vec::new();
+    let mut index = HashMap::new();
+    for frame in frames {
+        for markerset in frame.named_markersets() {
+            for i in 0..markerset.positions.len() {
+                index
+                    .entry((markerset.name.as_str(), i))
+                    .or_insert_with(|| {
+                        labels.push(format!("{}_{}", markerset.name, i + 1));
+                        labels.len() - 1
+                    });
+            }
+        }
+    }
+    let rows = frames
+        .iter()
+        .map(|frame| {
+            let mut row = vec![None; labels.len()];
+            for markerset in frame.named_markersets() {
+                for (i, pos) in markerset.positions.iter().enumerate() {
+                    if !pos.abs_diff_eq(Vec3::ZERO, OCCLUDED_EPSILON) {
+                        row[index[&(markerset.name.as_str(), i)]] = Some(*pos);
+                    }
+                }
+            }
+            row
+        })
+        .collect();
+    (labels, rows)
+}
+
+/* RigidBodyAsset */
+
+#[derive(Debug, Default)]
+pub struct RigidBodyAssetCodec {
+    pub strict: bool,
+}
+
+impl Encoder<RigidBodyAsset> for RigidBodyAssetCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: RigidBodyAsset, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // Reserve enough space for at least the id, pos, rot, marker error, and param
+        dst.reserve(38);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        dst.extend_from_slice(&bincode::serialize(&item.pos)?);
+        dst.extend_from_slice(&bincode::serialize(&item.rot)?);
+        dst.extend_from_slice(&item.marker_error.to_le_bytes()[..]);
+        dst.extend_from_slice(&(item.param).to_le_bytes()[..]);
+
+        Ok(())
+    }
+}
+
+impl Decoder for RigidBodyAssetCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = RigidBodyAsset;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        if src.remaining() < 38 {
+            return Err("Not enough bytes to decode RigidBodyAsset".into());
+        }
+
+        let id = src.get_u32_le();
+        let pos = Vec3 {
+            x: src.get_f32_le(),
+            y: src.get_f32_le(),
+            z: src.get_f32_le(),
+        };
+        let rot = Quat::from_xyzw(
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+        );
+        if self.strict {
+            ensure_finite("RigidBodyAsset.pos", pos.is_finite())?;
+        }
+        let rot = normalize_rot("RigidBodyAsset.rot", rot, self.strict)?;
+
+        let marker_error = src.get_f32_le();
+        let param = src.get_i16_le();
+        Ok(RigidBodyAsset {
+            id,
+            pos,
+            rot,
+            marker_error,
+            param,
+        })
+    }
+}
+
+impl DecodeInto for RigidBodyAssetCodec {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RigidBodyAsset {
+    pub id: u32,
+    pub pos: Vec3,
+    pub rot: Quat,
+    pub marker_error: f32,
+    pub param: i16,
+}
+
+impl RigidBodyAsset {
+    pub fn flags(&self) -> AssetFlags {
+        AssetFlags::from_param(self.param)
+    }
+}
+
+// Asset rigid body params use the rigid body bit layout; only tracking valid (0x01) is defined by
+// the NatNet SDK, so other bits are left in `param`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct AssetFlags {
+    pub param: i16,
+}
+
+impl AssetFlags {
+    pub fn from_param(param: i16) -> Self {
+        Self { param }
+    }
+
+    pub fn tracking_valid(&self) -> bool {
+        (self.param & 0x01) != 0
+    }
+}
+
+/* Skeleton */
+
+#[derive(Debug, Default)]
+pub struct SkeletonCodec {
+    pub strict: bool,
+}
+
+impl Encoder<Skeleton> for SkeletonCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: Skeleton, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the id and rigidbody count
+        dst.reserve(8);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        if item.rigid_body_count != item.rigid_bodies.len() as u32 {
+            log::warn!(
+                "RigidBody count {} does not match length of rigid_bodies vec {}",
+                item.rigid_body_count,
+                item.rigid_bodies.len()
+            );
+            dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
+        } else {
+            dst.extend_from_slice(&(item.rigid_bodies.len() as u32).to_le_bytes()[..]);
+        }
+        let mut rigid_body_codec = RigidBodyCodec::default();
+        for rb in item.rigid_bodies.into_iter() {
+            rigid_body_codec.encode(rb, dst)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for SkeletonCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = Skeleton;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut skeleton = Skeleton::default();
+        self.decode_into(src, &mut skeleton)?;
+        Ok(skeleton)
+    }
+}
+
+impl DecodeInto for SkeletonCodec {
+    fn decode_into(&mut self, src: &mut BytesMut, item: &mut Skeleton) -> Result<(), Self::Error> {
+        if src.remaining() < 8 {
+            return Err("Not enough bytes to decode Skeleton".into());
+        }
+        item.id = src.get_u32_le();
+        log::debug!("Skeleton ID: {}", item.id);
+        item.rigid_body_count = src.get_u32_le();
+        log::debug!("Skeleton RigidBody Count: {}", item.rigid_body_count);
+        let mut rigidbody_codec = RigidBodyCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut rigidbody_codec,
+            src,
+            item.rigid_body_count,
+            &mut item.rigid_bodies,
+        )?;
+        log::debug!("Skeleton RigidBodies: {:?}", item.rigid_bodies);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Skeleton {
+    pub id: u32,
+    pub rigid_body_count: u32,
+    pub rigid_bodies: Vec<RigidBody>,
+}
+
+#[derive(Debug, Default)]
+pub struct AssetCodec {
+    pub strict: bool,
+}
+
+impl Encoder<Asset> for AssetCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: Asset, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the id and rigidbody count
+        dst.reserve(8);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        if item.rigid_body_count != item.rigid_bodies.len() as u32 {
+            log::warn!(
+                "RigidBody count {} does not match length of rigid_bodies vec {}",
+                item.rigid_body_count,
+                item.rigid_bodies.len()
+            );
+            dst.extend_from_slice(&item.rigid_body_count.to_le_bytes()[..]);
+        } else {
+            dst.extend_from_slice(&(item.rigid_bodies.len() as u32).to_le_bytes()[..]);
+        }
+        let mut rigid_body_codec = RigidBodyAssetCodec::default();
+        for rb in item.rigid_bodies.into_iter() {
+            rigid_body_codec.encode(rb, dst)?;
+        }
+        let mut marker_asset_codec = MarkerAssetCodec::default();
+        marker_asset_codec.encode(item.markers, dst)?;
+        Ok(())
+    }
+}
+
+impl Decoder for AssetCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = Asset;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut asset = Asset::default();
+        self.decode_into(src, &mut asset)?;
+        Ok(asset)
+    }
+}
+
+impl DecodeInto for AssetCodec {
+    fn decode_into(&mut self, src: &mut BytesMut, item: &mut Asset) -> Result<(), Self::Error> {
+        if src.remaining() < 8 {
+            return Err("Not enough bytes to decode Asset".into());
+        }
+        item.id = src.get_u32_le();
+        item.rigid_body_count = src.get_u32_le();
+        let mut rigidbody_codec = RigidBodyAssetCodec {
+            strict: self.strict,
+        };
+        decode_elements_into(
+            &mut rigidbody_codec,
+            src,
+            item.rigid_body_count,
+            &mut item.rigid_bodies,
+        )?;
+        MarkerAssetCodec {
+            strict: self.strict,
+        }
+        .decode_into(src, &mut item.markers)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub id: u32,
+    pub rigid_body_count: u32,
+    pub rigid_bodies: Vec<RigidBodyAsset>,
+    pub markers: MarkerAsset,
+}
+
+/* LabeledMarker */
+
+// Bits of a labeled marker's params, as defined by the NatNet SDK
+const LABELED_MARKER_STATUS_MASK: u16 = 0x07;
+const OCCLUDED_FLAG: u16 = 0x01;
+const POINT_CLOUD_SOLVED_FLAG: u16 = 0x02;
+const MODEL_SOLVED_FLAG: u16 = 0x04;
+const ACTIVE_MARKER_FLAG: u16 = 0x20;
+
+#[derive(Debug, Default)]
+pub struct LabeledMarkerCodec {
+    pub strict: bool,
+}
+
+impl Encoder<LabeledMarker> for LabeledMarkerCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: LabeledMarker, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for entire struct
+        dst.reserve(26);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.size.to_le_bytes()[..]);
+        // `status` can't hold combined flags, so the params are only rewritten when it was changed
+        let params = if LabeledMarkerStatus::from_bits(item.params) == item.status {
+            item.params
+        } else {
+            (item.params & !LABELED_MARKER_STATUS_MASK) | item.status.to_bits()
+        };
+        dst.extend_from_slice(&params.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.residual.to_le_bytes()[..]);
+        Ok(())
+    }
+}
+
+impl Decoder for LabeledMarkerCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = LabeledMarker;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        if src.remaining() < 26 {
+            return Err("Not enough bytes to decode LabeledMarker".into());
+        }
+        let id = src.get_u32_le();
+        let pos = Vec3 {
+            x: src.get_f32_le(),
+            y: src.get_f32_le(),
+            z: src.get_f32_le(),
+        };
+        if self.strict {
+            ensure_finite("LabeledMarker.pos", pos.is_finite())?;
+        }
+        let size = src.get_f32_le();
+        let params = src.get_u16_le();
+        let status = LabeledMarkerStatus::from_bits(params);
+        let residual = src.get_f32_le();
+        Ok(LabeledMarker {
+            id,
+            pos,
+            size,
+            status,
+            params,
+            residual,
+        })
+    }
+}
+
+impl DecodeInto for LabeledMarkerCodec {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabeledMarker {
+    pub id: u32,
+    pub pos: Vec3,
+    pub size: f32,
+    // Lossy: the occluded, point cloud solved, and model solved flags are independent bits, and
+    // any combination of them (e.g. an occluded marker filled in by its model) is `Unrecognized`.
+    // Prefer the `is_*` predicates, which test the flags in `params`.
+    pub status: LabeledMarkerStatus,
+    // raw param flags; the status bits are also decoded into `status`, which takes precedence
+    // when encoding if it was changed
+    pub params: u16,
+    pub residual: f32,
+}
+
+impl LabeledMarker {
+    // the id packs the owning model id in the high 16 bits and the marker id in the low 16 bits
+    pub fn model_id(&self) -> u16 {
+        (self.id >> 16) as u16
+    }
+
+    pub fn marker_id(&self) -> u16 {
+        (self.id & 0xffff) as u16
+    }
+
+    pub fn is_occluded(&self) -> bool {
+        self.params & OCCLUDED_FLAG != 0
+    }
+
+    pub fn is_point_cloud_solved(&self) -> bool {
+        self.params & POINT_CLOUD_SOLVED_FLAG != 0
+    }
+
+    pub fn is_model_solved(&self) -> bool {
+        self.params & MODEL_SOLVED_FLAG != 0
+    }
+
+    // Active markers are powered LEDs that Motive identifies by their strobe pattern and flags in
+    // the params; passive (retroreflective) markers never set the flag
+    pub fn is_active_marker(&self) -> bool {
+        self.params & ACTIVE_MARKER_FLAG != 0
+    }
+
+    pub fn as_point(&self) -> Vec3 {
+        self.pos
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LabeledMarkerStatus {
+    Occluded,
+    PointCloudSolved,
+    ModelSolved,
+    Unrecognized,
+}
+
+impl LabeledMarkerStatus {
+    // Only the status bits are considered, so other param flags don't hide the status.  Exactly
+    // one status bit must be set; combinations such as occluded and model solved (0x05) are
+    // `Unrecognized`.
+    pub fn from_bits(bits: u16) -> Self {
+        match bits & LABELED_MARKER_STATUS_MASK {
+            OCCLUDED_FLAG => Self::Occluded,
+            POINT_CLOUD_SOLVED_FLAG => Self::PointCloudSolved,
+            MODEL_SOLVED_FLAG => Self::ModelSolved,
+            _ => Self::Unrecognized,
+        }
+    }
+
+    pub fn to_bits(self) -> u16 {
+        match self {
+            Self::Occluded => OCCLUDED_FLAG,
+            Self::PointCloudSolved => POINT_CLOUD_SOLVED_FLAG,
+            Self::ModelSolved => MODEL_SOLVED_FLAG,
+            Self::Unrecognized => 0x00,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ForcePlateCodec {}
+
+impl Encoder<ForcePlate> for ForcePlateCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: ForcePlate, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least id and channel count
+        dst.reserve(8);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.channel_count.to_le_bytes()[..]);
+        let mut force_plate_channel_codec = ForcePlateChannelCodec::default();
+        for ch in item.channels.into_iter() {
+            force_plate_channel_codec.encode(ch, dst)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for ForcePlateCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = ForcePlate;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut force_plate = ForcePlate::default();
+        self.decode_into(src, &mut force_plate)?;
+        Ok(force_plate)
+    }
+}
+
+impl DecodeInto for ForcePlateCodec {
+    fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        item: &mut ForcePlate,
+    ) -> Result<(), Self::Error> {
+        if src.remaining() < 8 {
+            return Err("Not enough bytes to decode ForcePlate".into());
+        }
+
+        item.id = src.get_u32_le();
+        item.channel_count = src.get_u32_le();
+        let mut force_plate_channel_codec = ForcePlateChannelCodec::default();
+        decode_elements_into(
+            &mut force_plate_channel_codec,
+            src,
+            item.channel_count,
+            &mut item.channels,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ForcePlate {
+    pub id: u32,
+    pub channel_count: u32,
+    pub channels: Vec<ForcePlateChannel>,
+}
+
+#[derive(Debug, Default)]
+pub struct ForcePlateChannelCodec {}
+
+impl Encoder<ForcePlateChannel> for ForcePlateChannelCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: ForcePlateChannel, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least value count and 1 value
+        dst.reserve(8);
+        dst.extend_from_slice(&item.value_count.to_le_bytes()[..]);
+        item.values
+            .into_iter()
+            .for_each(|v| dst.extend_from_slice(&v.to_le_bytes()[..]));
+        Ok(())
+    }
+}
+
+impl Decoder for ForcePlateChannelCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = ForcePlateChannel;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut channel = ForcePlateChannel::default();
+        self.decode_into(src, &mut channel)?;
+        Ok(channel)
+    }
+}
+
+impl DecodeInto for ForcePlateChannelCodec {
+    fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        item: &mut ForcePlateChannel,
+    ) -> Result<(), Self::Error> {
+        if src.remaining() < 4 {
+            return Err("Not enough bytes to decode ForcePlateChannel".into());
+        }
+        item.value_count = src.get_u32_le();
+        let expected = item.value_count as usize * 4;
+        if src.remaining() < expected {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "ForcePlateChannel values",
+                expected,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        item.values.clear();
+        item.values
+            .extend((0..item.value_count).map(|_| src.get_u32_le()));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ForcePlateChannel {
+    pub value_count: u32,
+    pub values: Vec<u32>,
+}
+
+// Some servers tag each device with a type between its id and channel count.  The frame decoder
+// detects this from the device section's byte count and sets `has_device_type` accordingly.
+#[derive(Debug, Default)]
+pub struct DeviceCodec {
+    pub has_device_type: bool,
+}
+
+impl DeviceCodec {
+    // Walks `count` devices in this codec's layout, returning the bytes they span, or `None` if
+    // they run past the end of `src`
+    fn section_len(&self, src: &[u8], count: u32) -> Option<usize> {
+        let mut buf = src;
+        let header_len = if self.has_device_type { 12 } else { 8 };
+        for _ in 0..count {
+            if buf.len() < header_len {
+                return None;
+            }
+            buf.advance(header_len - 4);
+            let channel_count = buf.get_u32_le();
+            for _ in 0..channel_count {
+                if buf.len() < 4 {
+                    return None;
+                }
+                let value_len = buf.get_u32_le() as usize * 4;
+                if buf.len() < value_len {
+                    return None;
+                }
+                buf.advance(value_len);
+            }
+        }
+        Some(src.len() - buf.len())
+    }
+}
+
+impl Encoder<Device> for DeviceCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: Device, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least id and channel count
+        dst.reserve(8);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        if let Some(device_type) = item.device_type {
+            dst.extend_from_slice(&device_type.to_le_bytes()[..]);
+        }
+        dst.extend_from_slice(&item.channel_count.to_le_bytes()[..]);
+        let mut device_channel_codec = DeviceChannelCodec::default();
+        for ch in item.channels.into_iter() {
+            device_channel_codec.encode(ch, dst)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for DeviceCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = Device;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut device = Device::default();
+        self.decode_into(src, &mut device)?;
+        Ok(device)
+    }
+}
+
+impl DecodeInto for DeviceCodec {
+    fn decode_into(&mut self, src: &mut BytesMut, item: &mut Device) -> Result<(), Self::Error> {
+        // must have at least an id, a channel count, and the type if tagged
+        let header_len = if self.has_device_type { 12 } else { 8 };
+        if src.remaining() < header_len {
+            return Err("Not enough bytes to decode Device".into());
+        }
+        item.id = src.get_u32_le();
+        item.device_type = self.has_device_type.then(|| src.get_u32_le());
+        item.channel_count = src.get_u32_le();
+        let mut device_channel_codec = DeviceChannelCodec::default();
+        decode_elements_into(
+            &mut device_channel_codec,
+            src,
+            item.channel_count,
+            &mut item.channels,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub id: u32,
+    pub device_type: Option<u32>,
+    pub channel_count: u32,
+    pub channels: Vec<DeviceChannel>,
+}
+
+#[derive(Debug, Default)]
+pub struct DeviceChannelCodec {}
+
+impl Encoder<DeviceChannel> for DeviceChannelCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: DeviceChannel, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least value count and a single value
+        dst.reserve(8);
+        dst.extend_from_slice(&item.value_count.to_le_bytes()[..]);
+        item.values
+            .into_iter()
+            .for_each(|v| dst.extend_from_slice(&v.to_le_bytes()[..]));
+        Ok(())
+    }
+}
+
+impl Decoder for DeviceChannelCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = DeviceChannel;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut channel = DeviceChannel::default();
+        self.decode_into(src, &mut channel)?;
+        Ok(channel)
+    }
+}
+
+impl DecodeInto for DeviceChannelCodec {
+    fn decode_into(
+        &mut self,
+        src: &mut BytesMut,
+        item: &mut DeviceChannel,
+    ) -> Result<(), Self::Error> {
+        // must have at least a count, and channels may have no values
+        if src.remaining() < 4 {
+            return Err("Not enough bytes to decode DeviceChannel".into());
+        }
+        item.value_count = src.get_u32_le();
+        let expected = item.value_count as usize * 4;
+        if src.remaining() < expected {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "DeviceChannel values",
+                expected,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        item.values.clear();
+        item.values
+            .extend((0..item.value_count).map(|_| src.get_u32_le()));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceChannel {
+    pub value_count: u32,
+    pub values: Vec<u32>,
+}
+
+impl DeviceChannel {
+    // Frames carry no sample rate.  A channel holds the analog samples taken during one mocap
+    // frame, so its rate is the sample count times the mocap frame rate configured in Motive, and
+    // channels sampled at different rates simply hold different counts.
+    pub fn sample_rate(&self, mocap_frame_rate: f32) -> f32 {
+        self.value_count as f32 * mocap_frame_rate
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StampsCodec {}
+
+impl Encoder<Stamps> for StampsCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: Stamps, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for entire struct
+        dst.reserve(32);
+        dst.extend_from_slice(&item.timestamp.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timestamp_mid.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timestamp_recv.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timestamp_tx.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timestamp_precision.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.timestamp_precision_fraction.to_le_bytes()[..]);
+        Ok(())
+    }
+}
+
+impl Decoder for StampsCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = Stamps;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        if src.remaining() < 40 {
+            return Err("Not enough bytes to decode Stamps".into());
+        }
+        let timestamp = src.get_f64_le();
+        log::debug!("Timestamp: {}", timestamp);
+        let timestamp_mid = src.get_i64_le();
+        log::debug!("Timestamp Mid: {}", timestamp_mid);
+        let timestamp_recv = src.get_i64_le();
+        log::debug!("Timestamp Recv: {}", timestamp_recv);
+        let timestamp_tx = src.get_i64_le();
+        log::debug!("Timestamp Tx: {}", timestamp_tx);
+        let timestamp_precision = src.get_i32_le();
+        log::debug!("Timestamp Precision: {}", timestamp_precision);
+        let timestamp_precision_fraction = src.get_i32_le();
+        log::debug!(
+            "Timestamp Precision Fraction: {}",
+            timestamp_precision_fraction
+        );
+
+        Ok(Stamps {
+            timestamp,
+            timestamp_mid,
+            timestamp_recv,
+            timestamp_tx,
+            timestamp_precision,
+            timestamp_precision_fraction,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamps {
+    pub timestamp: f64,
+    pub timestamp_mid: i64,
+    pub timestamp_recv: i64,
+    pub timestamp_tx: i64,
+    pub timestamp_precision: i32,
+    pub timestamp_precision_fraction: i32,
+}
+
+impl Default for Stamps {
+    fn default() -> Self {
+        Self {
+            timestamp: 0.0,
+            timestamp_mid: 0,
+            timestamp_recv: 0,
+            timestamp_tx: 0,
+            timestamp_precision: 0,
+            timestamp_precision_fraction: 0,
+        }
+    }
+}
+
+impl Stamps {
+    // `timestamp` is when the frame started, in seconds since Motive began streaming, while
+    // `timestamp_mid` is the middle of the cameras' exposure in ticks of the server's high
+    // resolution clock.  Mid-exposure is when the markers were actually where they were seen, so
+    // it's what should be used to align mocap with other sensors.  NatNet reports the clock's
+    // tick rate as the server's high resolution clock frequency.
+    pub fn mid_exposure_time(&self, clock_frequency: f64) -> f64 {
+        self.timestamp_mid as f64 / clock_frequency
+    }
+}
+
+// Writes the trajectory of rigid body `id` in the TUM format used by evaluation tools such as evo,
+// one `timestamp tx ty tz qx qy qz qw` line per frame.  Frames where the body is missing or not
+// tracked are skipped.  Returns the number of lines written.
+pub fn write_tum_trajectory<W: io::Write>(
+    w: &mut W,
+    id: u32,
+    frames: &[FrameData],
+) -> io::Result<usize> {
+    let mut lines = 0;
+    for frame in frames {
+        let Some(rb) = frame
+            .rigid_bodies
+            .iter()
+            .find(|rb| rb.id == id && rb.is_tracking_valid)
+        else {
+            continue;
+        };
+        writeln!(
+            w,
+            "{:.6} {} {} {} {} {} {} {}",
+            frame.stamps.timestamp,
+            rb.pos.x,
+            rb.pos.y,
+            rb.pos.z,
+            rb.rot.x,
+            rb.rot.y,
+            rb.rot.z,
+            rb.rot.w
+        )?;
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+// Earliest and latest `Stamps::timestamp` across `frames`, e.g. for a capture's duration.  Frames
+// with default stamps weren't stamped by the server (or had no stamps), so they're skipped.
+pub fn timestamp_range(frames: &[FrameData]) -> Option<(f64, f64)> {
+    frames
+        .iter()
+        .filter(|frame| frame.stamps != Stamps::default())
+        .map(|frame| frame.stamps.timestamp)
+        .fold(None, |range, t| match range {
+            None => Some((t, t)),
+            Some((min, max)) => Some((f64::min(min, t), f64::max(max, t))),
+        })
+}
+
+#[derive(Debug, Default)]
+pub struct FrameParametersCodec {}
+
+impl Encoder<FrameParameters> for FrameParametersCodec {
+    type Error = Box<dyn error::Error>;
+    fn encode(&mut self, item: FrameParameters, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least value count and 1 value
+        dst.reserve(2);
+        dst.extend_from_slice(&item.param.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl Decoder for FrameParametersCodec {
+    type Error = Box<dyn error::Error>;
+    type Item = FrameParameters;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        if src.remaining() < 2 {
+            return Err("Not enough bytes to decode FrameParameters".into());
+        }
+        let param = src.get_i16_le();
+        log::debug!("Param: {}", param);
+        let is_recording = (param & 0x01) != 0;
+        log::debug!("Is Recording: {}", is_recording);
+        let tracked_models_changed = (param & 0x02) != 0;
+        log::debug!("Tracking Models Changed: {}", tracked_models_changed);
+        // bit 4 (0x10): continuous calibration detected a camera bump
+        let bump_detected = (param & 0x10) != 0;
+        log::debug!("Bump Detected: {}", bump_detected);
+        Ok(FrameParameters {
+            param,
+            is_recording,
+            tracked_models_changed,
+            bump_detected,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrameParameters {
+    pub param: i16,
+    pub is_recording: bool,
+    pub tracked_models_changed: bool,
+    pub bump_detected: bool,
+}
+
+/* SmpteTimecode */
+
+// Flag in the frame byte of the timecode word marking drop-frame (29.97/59.94fps) timecode
+const DROP_FRAME_FLAG: u32 = 0x80;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SmpteTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub subframe: u32,
+    pub drop_frame: bool,
+}
+
+impl SmpteTimecode {
+    // Unpacks the `hh:mm:ss:ff` timecode word and subframe from `FrameData`
+    pub fn from_raw(timecode: u32, timecode_sub: u32) -> Self {
+        Self {
+            hours: (timecode >> 24) as u8,
+            minutes: (timecode >> 16) as u8,
+            seconds: (timecode >> 8) as u8,
+            frames: (timecode & !DROP_FRAME_FLAG & 0xff) as u8,
+            subframe: timecode_sub,
+            drop_frame: (timecode & DROP_FRAME_FLAG) != 0,
+        }
+    }
+
+    pub fn to_raw(&self) -> (u32, u32) {
+        let mut timecode = (self.hours as u32) << 24
+            | (self.minutes as u32) << 16
+            | (self.seconds as u32) << 8
+            | self.frames as u32;
+        if self.drop_frame {
+            timecode |= DROP_FRAME_FLAG;
+        }
+        (timecode, self.subframe)
+    }
+
+    // Elapsed seconds at the nominal frame rate `fps` (e.g. 30 for 29.97 drop-frame).  Drop-frame
+    // timecode skips `fps / 15` frame numbers every minute except every tenth minute, and runs at
+    // `fps * 1000 / 1001` frames per second.
+    pub fn timecode_seconds(&self, fps: u32) -> f64 {
+        let fps = fps as u64;
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let nominal_frames = (total_minutes * 60 + self.seconds as u64) * fps + self.frames as u64;
+        if self.drop_frame {
+            let dropped = (fps / 15) * (total_minutes - total_minutes / 10);
+            (nominal_frames - dropped) as f64 * 1001.0 / (fps as f64 * 1000.0)
+        } else {
+            nominal_frames as f64 / fps as f64
+        }
+    }
+}
+
+/* MarkerSetDesc */
+
+#[derive(Debug, Default)]
+pub struct MarkerSetDescCodec;
+
+impl Encoder<MarkerSetDesc> for MarkerSetDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: MarkerSetDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the name, marker count, and a single position
+        dst.reserve(item.name.len() + 16);
+        dst.extend_from_slice(item.name.as_bytes());
+        // end string with null terminator
+        dst.put_u8(0);
+        if item.marker_count != item.marker_names.len() as i32 {
+            log::warn!(
+                "Marker count {} does not match length of marker vec {}",
+                item.marker_count,
+                item.marker_names.len()
+            );
+            dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
+        } else {
+            dst.extend_from_slice(&(item.marker_names.len() as i32).to_le_bytes()[..]);
+        }
+        item.marker_names.iter().for_each(|n| {
+            dst.extend_from_slice(n.as_bytes());
+            dst.put_u8(0);
+        });
+        Ok(())
+    }
+}
+
+impl Decoder for MarkerSetDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    type Item = MarkerSetDesc;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut name_buf = Vec::new();
+        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+        if name_buf.last() == Some(&b'\0') {
+            name_buf.pop();
+        }
+        let name = String::from_utf8(name_buf)?;
+
+        if src.remaining() < 16 {
+            let msg = "Not enough bytest to decode MarkerSetDesc";
+            log::error!("{}", msg);
+            return Err(msg.into());
+        }
+        log::debug!("MarkerSet name: '{}'", name);
+
+        let marker_count = src.get_i32_le();
+        log::debug!("Marker count: {}", marker_count);
+
+        let mut marker_names = Vec::new();
+        for _ in 0..marker_count {
+            let mut name_buf = Vec::new();
+            let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+            if name_buf.last() == Some(&b'\0') {
+                name_buf.pop();
+            }
+            marker_names.push(String::from_utf8(name_buf)?);
+        }
+
+        Ok(Self::Item {
+            name,
+            marker_count,
+            marker_names,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkerSetDesc {
+    pub name: String,
+    pub marker_count: i32,
+    pub marker_names: Vec<String>,
+}
+
+impl MarkerSetDesc {
+    pub fn new(name: &str, marker_count: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            marker_count,
+            marker_names: Vec::new(),
+        }
+    }
+}
+
+/* RigidBodyDesc */
+
+// The description layout depends on the NatNet version:
+// - all versions: name, id, parent id, and offset from the parent
+// - 3.0+: marker count, then every marker offset, then every marker's active label
+// - 4.0+: the marker names follow the active labels
+// Older descriptions decode with no markers.
+#[derive(Debug)]
+pub struct RigidBodyDescCodec {
+    pub version: NatNetVersion,
+}
+
+impl RigidBodyDescCodec {
+    pub fn new(version: NatNetVersion) -> Self {
+        Self { version }
+    }
+
+    fn has_markers(&self) -> bool {
+        self.version >= NatNetVersion::new(3, 0, 0, 0)
+    }
+
+    fn has_marker_names(&self) -> bool {
+        self.version >= NatNetVersion::new(4, 0, 0, 0)
+    }
+}
+
+impl Default for RigidBodyDescCodec {
+    fn default() -> Self {
+        Self::new(VERSION.into())
+    }
+}
+
+impl Encoder<RigidBodyDesc> for RigidBodyDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: RigidBodyDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the id, pos, and rot
+        //dst.reserve(38);
+        dst.extend_from_slice(item.name.as_bytes());
+        // end string with null terminator
+        dst.put_u8(0);
+        dst.extend_from_slice(&item.id.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.parent_id.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
+        if !self.has_markers() {
+            return Ok(());
+        }
+        dst.extend_from_slice(&item.marker_count.to_le_bytes()[..]);
+        item.marker_offsets.iter().for_each(|m| {
+            dst.extend_from_slice(&m.x.to_le_bytes()[..]);
+            dst.extend_from_slice(&m.y.to_le_bytes()[..]);
+            dst.extend_from_slice(&m.z.to_le_bytes()[..]);
+        });
+        item.marker_active_labels.iter().for_each(|m| {
+            dst.extend_from_slice(&m.to_le_bytes()[..]);
+        });
+        if self.has_marker_names() {
+            item.marker_names.iter().for_each(|m| {
+                dst.extend_from_slice(m.as_bytes());
+                dst.put_u8(0);
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for RigidBodyDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    type Item = RigidBodyDesc;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut name_buf = Vec::new();
+        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+        if name_buf.last() == Some(&b'\0') {
+            name_buf.pop();
+        }
+        let name = String::from_utf8(name_buf)?;
+        log::debug!("RigidBodyDesc name: '{}'", name);
+
+        // id, parent id, pos, and marker count
+        let expected = if self.has_markers() { 24 } else { 20 };
+        if src.remaining() < expected {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "RigidBodyDesc",
+                expected,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let id = src.get_i32_le();
+        let parent_id = src.get_i32_le();
+
+        let pos = Vec3 {
+            x: src.get_f32_le(),
+            y: src.get_f32_le(),
+            z: src.get_f32_le(),
+        };
+
+        if !self.has_markers() {
+            return Ok(RigidBodyDesc {
+                name,
+                id,
+                parent_id,
+                pos,
+                marker_count: 0,
+                marker_offsets: Vec::new(),
+                marker_active_labels: Vec::new(),
+                marker_names: Vec::new(),
+            });
+        }
+
+        let marker_count = src.get_i32_le();
+
+        // each marker has a 12 byte offset and a 4 byte active label ahead of the names
+        let marker_bytes = marker_count.max(0) as usize * 16;
+        if src.remaining() < marker_bytes {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "RigidBodyDesc markers",
+                expected: marker_bytes,
+                got: src.remaining(),
+            }
+            .into());
+        }
+
+        let marker_offsets = (0..marker_count)
+            .map(|_| Vec3 {
+                x: src.get_f32_le(),
+                y: src.get_f32_le(),
+                z: src.get_f32_le(),
+            })
+            .collect();
+
+        let marker_active_labels = (0..marker_count).map(|_| src.get_i32_le()).collect();
+
+        let mut marker_names = Vec::new();
+        if self.has_marker_names() {
+            for _ in 0..marker_count {
+                let mut name_buf = Vec::new();
+                let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+                if name_buf.last() == Some(&b'\0') {
+                    name_buf.pop();
+                }
+                marker_names.push(String::from_utf8(name_buf)?);
+            }
+        }
+
+        Ok(RigidBodyDesc {
+            name,
+            id,
+            parent_id,
+            pos,
+            marker_count,
+            marker_offsets,
+            marker_active_labels,
+            marker_names,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RigidBodyDesc {
+    pub name: String,
+    pub id: i32,
+    pub parent_id: i32,
+    pub pos: Vec3,
+    pub marker_count: i32,
+    pub marker_offsets: Vec<Vec3>,
+    pub marker_active_labels: Vec<i32>,
+    pub marker_names: Vec<String>,
+}
+
+impl RigidBodyDesc {
+    pub fn world_marker_positions(&self, body: &RigidBody) -> Vec<Vec3> {
+        self.marker_offsets
+            .iter()
+            .map(|offset| body.pos + body.rot * *offset)
+            .collect()
+    }
+
+    // Least squares fit of the pose mapping `marker_offsets` onto `observed_markers` (in the same
+    // order), i.e. the inverse of `world_marker_positions`.  Solves the Kabsch problem with Horn's
+    // quaternion method, which always yields a proper rotation.  Returns `None` unless there's an
+    // observation for every offset, or when the markers are too close to collinear to fix the
+    // rotation.
+    pub fn fit_pose(&self, observed_markers: &[Vec3]) -> Option<(Vec3, Quat)> {
+        let fit = PointSetFit::solve(&self.marker_offsets, observed_markers)?;
+        let pos = fit.to_centroid - fit.rot * fit.from_centroid;
+        Some((pos.as_vec3(), fit.rot.as_quat()))
+    }
+}
+
+// Least squares similarity transform between corresponding point sets, `to ~ scale * rot * from
+// + translation`, in the style of Umeyama: the rotation comes from Horn's quaternion method and
+// the scale from the rotated cross covariance.  Returns `None` when there are fewer than 3 points,
+// the sets differ in length, or the points are too close to collinear to fix the rotation.
+pub fn align_frames(mocap_points: &[Vec3], robot_points: &[Vec3]) -> Option<(Vec3, Quat, f32)> {
+    let fit = PointSetFit::solve(mocap_points, robot_points)?;
+    let translation = fit.to_centroid - fit.scale * (fit.rot * fit.from_centroid);
+    Some((translation.as_vec3(), fit.rot.as_quat(), fit.scale as f32))
+}
+
+struct PointSetFit {
+    from_centroid: DVec3,
+    to_centroid: DVec3,
+    rot: glam::DQuat,
+    scale: f64,
+}
+
+impl PointSetFit {
+    fn solve(from: &[Vec3], to: &[Vec3]) -> Option<Self> {
+        let count = from.len();
+        if count < 3 || to.len() != count {
+            return None;
+        }
+        let widen = |v: &Vec3| v.as_dvec3();
+        let from_centroid = from.iter().map(widen).sum::<DVec3>() / count as f64;
+        let to_centroid = to.iter().map(widen).sum::<DVec3>() / count as f64;
+
+        // cross covariance, s[i][j] = sum of from[i] * to[j]
+        let mut s = [[0.0_f64; 3]; 3];
+        let mut spread = 0.0;
+        let mut from_spread = 0.0;
+        for (from, to) in from.iter().zip(to) {
+            let p = (widen(from) - from_centroid).to_array();
+            let q = (widen(to) - to_centroid).to_array();
+            for i in 0..3 {
+                for j in 0..3 {
+                    s[i][j] += p[i] * q[j];
+                }
+            }
+            from_spread += p.iter().map(|v| v * v).sum::<f64>();
+            spread += p.iter().chain(q.iter()).map(|v| v * v).sum::<f64>();
+        }
+        let [[sxx, sxy, sxz], [syx, syy, syz], [szx, szy, szz]] = s;
+        let n = [
+            [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+            [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+            [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+            [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+        ];
+        let (eigenvalues, eigenvectors) = symmetric_eigen4(n);
+        let mut order = [0, 1, 2, 3];
+        order.sort_by(|a, b| eigenvalues[*b].total_cmp(&eigenvalues[*a]));
+        // a repeated largest eigenvalue means the rotation about some axis is unconstrained
+        if eigenvalues[order[0]] - eigenvalues[order[1]] <= 1e-9 * spread.max(f64::MIN_POSITIVE) {
+            return None;
+        }
+        let [w, x, y, z] = eigenvectors[order[0]];
+        // the largest eigenvalue is the sum of to . (rot * from) over the centered points
+        Some(Self {
+            from_centroid,
+            to_centroid,
+            rot: glam::DQuat::from_xyzw(x, y, z, w).normalize(),
+            scale: eigenvalues[order[0]] / from_spread,
+        })
+    }
+}
+
+// Eigenvalues and unit eigenvectors of a symmetric 4x4 matrix by cyclic Jacobi rotations
+fn symmetric_eigen4(mut a: [[f64; 4]; 4]) -> ([f64; 4], [[f64; 4]; 4]) {
+    // columns of `v` accumulate the rotations and end up as the eigenvectors
+    let mut v = [[0.0; 4]; 4];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..64 {
+        let off_diagonal: f64 = (0..4)
+            .flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-30 {
+            break;
+        }
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+                for row in v.iter_mut() {
+                    let (vp, vq) = (row[p], row[q]);
+                    row[p] = c * vp - s * vq;
+                    row[q] = s * vp + c * vq;
+                }
+            }
+        }
+    }
+    let eigenvalues = [a[0][0], a[1][1], a[2][2], a[3][3]];
+    let mut eigenvectors = [[0.0; 4]; 4];
+    for (i, eigenvector) in eigenvectors.iter_mut().enumerate() {
+        for (k, row) in v.iter().enumerate() {
+            eigenvector[k] = row[i];
+        }
+    }
+    (eigenvalues, eigenvectors)
+}
+
+/* CameraDesc */
+
+#[derive(Debug, Default)]
+pub struct CameraDescCodec;
+
+impl Encoder<CameraDesc> for CameraDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: CameraDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // reserve enough space for at least the id, pos, and rot
+        dst.reserve(item.name.len() + 29);
+        dst.extend_from_slice(item.name.as_bytes());
+        // end string with null terminator
+        dst.put_u8(0);
+        dst.extend_from_slice(&item.pos.x.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.y.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.pos.z.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.rot.x.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.rot.y.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.rot.z.to_le_bytes()[..]);
+        dst.extend_from_slice(&item.rot.w.to_le_bytes()[..]);
+        if let Some(id) = item.id {
+            dst.extend_from_slice(&id.to_le_bytes()[..]);
+            if let Some([width, height]) = item.resolution {
+                dst.extend_from_slice(&width.to_le_bytes()[..]);
+                dst.extend_from_slice(&height.to_le_bytes()[..]);
+                if let Some(status) = item.status {
+                    dst.put_u32_le(status.status);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for CameraDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    type Item = CameraDesc;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut name_buf = Vec::new();
+        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+        if name_buf.last() == Some(&b'\0') {
+            name_buf.pop();
+        }
+        let name = String::from_utf8(name_buf)?;
+        log::debug!("CameraDesc name: {}", name);
+
+        // pos and rot
+        if src.remaining() < 28 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "CameraDesc",
+                expected: 28,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let pos = Vec3 {
+            x: src.get_f32_le(),
+            y: src.get_f32_le(),
+            z: src.get_f32_le(),
+        };
+        log::debug!("CameraDesc pos: {}", pos);
+
+        let rot = Quat::from_xyzw(
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+            src.get_f32_le(),
+        );
+        log::debug!("CameraDesc rot: {}", rot);
+
+        let id = (src.remaining() >= 4).then(|| src.get_i32_le());
+        log::debug!("CameraDesc id: {:?}", id);
+        let resolution = (src.remaining() >= 8).then(|| [src.get_i32_le(), src.get_i32_le()]);
+        log::debug!("CameraDesc resolution: {:?}", resolution);
+        let status = (src.remaining() >= 4).then(|| CameraStatus::from_bits(src.get_u32_le()));
+        log::debug!("CameraDesc status: {:?}", status);
+
+        Ok(CameraDesc {
+            name,
+            pos,
+            rot,
+            id,
+            resolution,
+            status,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraDesc {
+    pub name: String,
+    pub pos: Vec3,
+    pub rot: Quat,
+    // Appended after the orientation by servers newer than NatNet 4.1, so `None` when decoding
+    // older captures
+    pub id: Option<i32>,
+    pub resolution: Option<[i32; 2]>,
+    // Trails the resolution on servers that report camera health
+    pub status: Option<CameraStatus>,
+}
+
+// Raw camera status bits, so undefined bits round-trip unchanged
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CameraStatus {
+    pub status: u32,
+}
+
+impl CameraStatus {
+    pub fn from_bits(status: u32) -> Self {
+        Self { status }
+    }
+
+    // bit 0 (0x01): the camera is enabled for tracking
+    pub fn enabled(&self) -> bool {
+        (self.status & 0x01) != 0
+    }
+
+    // bit 1 (0x02): the camera is in reference (video) mode rather than tracking
+    pub fn is_reference(&self) -> bool {
+        (self.status & 0x02) != 0
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AssetDescCodec;
+
+impl Encoder<AssetDesc> for AssetDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    fn encode(&mut self, item: AssetDesc, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.reserve(item.name.len() + item.descriptions.len() + 17);
+        dst.extend_from_slice(item.name.as_bytes());
+        dst.put_u8(0);
+        dst.put_i32_le(item.asset_type);
+        dst.put_i32_le(item.id);
+        if item.descriptions.is_empty() {
+            // no rigid body or marker descriptions
+            dst.put_i32_le(0);
+            dst.put_i32_le(0);
+        } else {
+            dst.extend_from_slice(&item.descriptions);
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for AssetDescCodec {
+    type Error = Box<dyn std::error::Error>;
+    type Item = AssetDesc;
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Self::Item, Self::Error> {
+        let mut name_buf = Vec::new();
+        let _len = src.reader().read_until(b'\0', &mut name_buf)?;
+        if name_buf.last() == Some(&b'\0') {
+            name_buf.pop();
+        }
+        let name = String::from_utf8(name_buf)?;
+        log::debug!("AssetDesc name: {}", name);
+
+        if src.remaining() < 8 {
+            return Err(DecodeError::NotEnoughBytes {
+                item: "AssetDesc",
+                expected: 8,
+                got: src.remaining(),
+            }
+            .into());
+        }
+        let asset_type = src.get_i32_le();
+        log::debug!("AssetDesc type: {}", asset_type);
+        let id = src.get_i32_le();
+        log::debug!("AssetDesc id: {}", id);
+        let descriptions = src.split().freeze();
+
+        Ok(AssetDesc {
+            name,
+            asset_type,
+            id,
+            descriptions,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetDesc {
+    pub name: String,
+    pub asset_type: i32,
+    pub id: i32,
+    // the rigid body and marker descriptions, kept raw so they re-encode unchanged
+    pub descriptions: Bytes,
+}
+
+/* NatNetClient */
+
+pub const DEFAULT_COMMAND_PORT: u16 = 1510;
+pub const DEFAULT_DATA_PORT: u16 = 1511;
+pub const DEFAULT_MULTICAST_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 42, 99);
+
+// Large enough for any UDP datagram
+const MAX_PACKET_SIZE: usize = 65536;
+
+// Control messages kept for `NatNetClient::poll_control`; the oldest are dropped past this
+const CONTROL_QUEUE_LEN: usize = 64;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransportMode {
+    Multicast {
+        group: Ipv4Addr,
+    },
+    // `interface_index` 0 lets the OS pick the interface
+    MulticastV6 {
+        group: Ipv6Addr,
+        interface_index: u32,
+    },
+    // frame data is sent to the address our data socket registers with a keep alive
+    Unicast,
+}
+
+#[derive(Debug, Clone)]
+pub struct NatNetConfig {
+    pub server_address: IpAddr,
+    pub local_address: IpAddr,
+    pub command_port: u16,
+    pub data_port: u16,
+    pub transport: TransportMode,
+    pub connect_timeout: Duration,
+    // `None` blocks in `NatNetClient::recv` until data arrives
+    pub read_timeout: Option<Duration>,
+    // request the model definitions again when a frame reports the tracked models changed, and
+    // return them from the next `NatNetClient::recv`
+    pub auto_refresh_model_def: bool,
+}
+
+impl Default for NatNetConfig {
+    fn default() -> Self {
+        Self {
+            server_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            local_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            command_port: DEFAULT_COMMAND_PORT,
+            data_port: DEFAULT_DATA_PORT,
+            transport: TransportMode::Multicast {
+                group: DEFAULT_MULTICAST_GROUP,
+            },
+            connect_timeout: Duration::from_secs(2),
+            read_timeout: None,
+            auto_refresh_model_def: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NatNetClient {
+    config: NatNetConfig,
+    command_socket: UdpSocket,
+    data_socket: UdpSocket,
+    server_info: PingResponse,
+    codec: FrameDataCodec,
+    buf: Vec<u8>,
+    models_changed: bool,
+    pending: Option<Message>,
+    control: VecDeque<Message>,
+}
+
+impl NatNetClient {
+    pub fn connect(config: NatNetConfig) -> Result<Self, Box<dyn error::Error>> {
+        let (command_socket, server_info, buf) = Self::handshake(&config)?;
+        let server = SocketAddr::new(config.server_address, config.command_port);
+
+        let data_socket = match config.transport {
+            TransportMode::Multicast { group } => {
+                let IpAddr::V4(interface) = config.local_address else {
+                    return Err("IPv4 multicast requires an IPv4 local address".into());
+                };
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, config.data_port))?;
+                socket.join_multicast_v4(&group, &interface)?;
+                socket
+            }
+            TransportMode::MulticastV6 {
+                group,
+                interface_index,
+            } => {
+                let socket = UdpSocket::bind((Ipv6Addr::UNSPECIFIED, config.data_port))?;
+                socket.join_multicast_v6(&group, interface_index)?;
+                socket
+            }
+            TransportMode::Unicast => {
+                let socket = UdpSocket::bind((config.local_address, 0))?;
+                socket.send_to(&keepalive_packet(), server)?;
+                socket
+            }
+        };
+        data_socket.set_read_timeout(config.read_timeout)?;
+
+        let version = NatNetVersion::from(server_info.natnet_version);
+        Ok(Self {
+            config,
+            command_socket,
+            data_socket,
+            server_info,
+            codec: FrameDataCodec::new(version),
+            buf,
+            models_changed: false,
+            pending: None,
+            control: VecDeque::new(),
+        })
+    }
+
+    // Pings the server from a fresh command socket
+    fn handshake(
+        config: &NatNetConfig,
+    ) -> Result<(UdpSocket, PingResponse, Vec<u8>), Box<dyn error::Error>> {
+        let server = SocketAddr::new(config.server_address, config.command_port);
+        log::debug!("Connecting to {}", server);
+        let command_socket = UdpSocket::bind((config.local_address, 0))?;
+        command_socket.connect(server)?;
+        command_socket.set_read_timeout(Some(config.connect_timeout))?;
+        command_socket.send(&connect_packet())?;
+
+        let mut buf = vec![0u8; MAX_PACKET_SIZE];
+        let len = command_socket.recv(&mut buf)?;
+        let server_info = match Message::from_bytes(&buf[..len])? {
+            Message::PingResponse(server_info) => *server_info,
+            val => return Err(format!("Expected PingResponse, got {:?}", val).into()),
+        };
+        log::info!(
+            "Connected to {} {:?} using NatNet {}",
+            server_info.app_name,
+            server_info.server_version,
+            NatNetVersion::from(server_info.natnet_version)
+        );
+        Ok((command_socket, server_info, buf))
+    }
+
+    // Repeats the handshake, e.g. after the server sends `Message::DisconnectByTimeout`.
+    // The data socket is kept, so a fixed multicast port is not rebound while still in use.
+    pub fn reconnect(&mut self) -> Result<(), Box<dyn error::Error>> {
+        let (command_socket, server_info, buf) = Self::handshake(&self.config)?;
+        if matches!(self.config.transport, TransportMode::Unicast) {
+            let server = SocketAddr::new(self.config.server_address, self.config.command_port);
+            self.data_socket.send_to(&keepalive_packet(), server)?;
+        }
+        self.command_socket = command_socket;
+        self.codec = FrameDataCodec::new(NatNetVersion::from(server_info.natnet_version));
+        self.server_info = server_info;
+        self.buf = buf;
+        self.models_changed = false;
+        self.pending = None;
+        self.control.clear();
+        Ok(())
+    }
+
+    pub fn server_info(&self) -> &PingResponse {
+        &self.server_info
+    }
+
+    pub fn codec(&self) -> &FrameDataCodec {
+        &self.codec
+    }
+
+    pub fn set_read_timeout(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Box<dyn error::Error>> {
+        self.data_socket.set_read_timeout(timeout)?;
+        self.config.read_timeout = timeout;
+        Ok(())
+    }
+
+    pub fn send_keepalive(&self) -> Result<(), Box<dyn error::Error>> {
+        self.command_socket.send(&keepalive_packet())?;
+        Ok(())
+    }
+
+    pub fn request_model_def(&mut self) -> Result<ModelDef, Box<dyn error::Error>> {
+        self.command_socket.send(&request_modeldef_packet())?;
+        let len = self.command_socket.recv(&mut self.buf)?;
+        let src = &self.buf[..len];
+        match Message::peek_id(src) {
+            Some(MessageId::ModelDef) => {
+                let mut bytes = BytesMut::from(&src[2..]);
+                Ok(ModelDefCodec::new(self.codec.version).decode(&mut bytes)?)
+            }
+            id => Err(format!("Expected ModelDef, got {:?}", id).into()),
+        }
+    }
+
+    pub fn recv(&mut self) -> Result<Message, Box<dyn error::Error>> {
+        if let Some(message) = self.pending.take() {
+            return Ok(message);
+        }
+        let len = match self.data_socket.recv(&mut self.buf) {
+            Ok(len) => len,
+            Err(e) => match e.kind() {
+                // the error kind for an expired read timeout is platform specific
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                    return Err(DecodeError::Timeout.into())
+                }
+                _ => return Err(e.into()),
+            },
+        };
+        let src = &self.buf[..len];
+        match Message::peek_id(src) {
+            Some(MessageId::FrameData) => {
+                let mut bytes = BytesMut::from(&src[2..]);
+                let frame_data = self.codec.decode(&mut bytes)?;
+                self.refresh_model_def(frame_data.tracking_models_changed());
+                Ok(Message::FrameData(Box::new(frame_data)))
+            }
+            _ => Message::from_bytes(src),
+        }
+    }
+
+    // Yields each received frame.  Some servers multiplex command responses (server info, model
+    // definitions, responses) onto the data port, so any other message is queued for
+    // `poll_control` instead.  Receive errors (including timeouts) are yielded and the stream goes
+    // on.
+    pub fn frames(
+        &mut self,
+    ) -> impl Iterator<Item = Result<FrameData, Box<dyn error::Error>>> + '_ {
+        std::iter::from_fn(move || loop {
+            match self.recv() {
+                Ok(Message::FrameData(frame)) => return Some(Ok(*frame)),
+                Ok(message) => {
+                    if self.control.len() == CONTROL_QUEUE_LEN {
+                        log::warn!("Control queue full, dropping {:?}", self.control.front());
+                        self.control.pop_front();
+                    }
+                    self.control.push_back(message);
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+
+    // The oldest non-frame message received by `frames`
+    pub fn poll_control(&mut self) -> Option<Message> {
+        self.control.pop_front()
+    }
+
+    // Moves the client to a thread that sends each received frame on the returned channel,
+    // skipping other messages.  Receive errors (including timeouts) are sent too, and the thread
+    // stops after a socket error or once a send finds the receiver dropped.  Without a read
+    // timeout that's only noticed when the next frame arrives.
+    pub fn spawn_reader(mut self) -> (JoinHandle<()>, Receiver<Result<FrameData, DecodeError>>) {
+        let (tx, rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || loop {
+            let (item, fatal) = match self.recv() {
+                Ok(Message::FrameData(frame)) => (Ok(*frame), false),
+                Ok(message) => {
+                    log::debug!("Reader skipping {:?}", message);
+                    continue;
+                }
+                Err(e) => {
+                    let e = DecodeError::from(e);
+                    let fatal = matches!(e, DecodeError::Io { .. });
+                    (Err(e), fatal)
+                }
+            };
+            if tx.send(item).is_err() || fatal {
+                break;
+            }
+        });
+        (handle, rx)
+    }
+
+    // Yields the markerset named `name` from each received frame, skipping frames without it and
+    // any other messages.  Receive errors (including timeouts) are yielded and the stream goes on.
+    pub fn markerset_stream<'a>(
+        &'a mut self,
+        name: &'a str,
+    ) -> impl Iterator<Item = Result<MarkerSet, Box<dyn error::Error>>> + 'a {
+        std::iter::from_fn(move || loop {
+            match self.recv() {
+                Ok(Message::FrameData(frame)) => {
+                    if let Some(markerset) = frame.markersets.into_iter().find(|ms| ms.name == name)
+                    {
+                        return Some(Ok(markerset));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+
+    // Requests the model definitions when the tracked models changed flag is raised.  A failed
+    // request is retried on the next frame that still has the flag set.
+    fn refresh_model_def(&mut self, models_changed: bool) {
+        if self.config.auto_refresh_model_def && models_changed && !self.models_changed {
+            match self.request_model_def() {
+                Ok(model_def) => self.pending = Some(Message::ModelDef(Box::new(model_def))),
+                Err(e) => {
+                    log::warn!("Failed to refresh model definitions: {}", e);
+                    return;
+                }
+            }
+        }
+        self.models_changed = models_changed;
+    }
+}
+
+/* Approx */
+
+// Float-tolerant comparisons for decoded poses, for use in downstream test suites as well
+pub mod approx {
+    use glam::{Quat, Vec3};
+
+    pub fn vec3_approx_eq(a: Vec3, b: Vec3, eps: f32) -> bool {
+        a.abs_diff_eq(b, eps)
+    }
+
+    // q and -q are the same rotation, so either sign matches
+    pub fn quat_approx_eq(a: Quat, b: Quat, eps: f32) -> bool {
+        a.abs_diff_eq(b, eps) || a.abs_diff_eq(-b, eps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A FrameData message captured from Motive
+    const SAMPLE_FRAME_DATA: &[u8] = include_bytes!("FrameData.bin");
+
+    fn init() {
+        let _ = env_logger::builder()
+            .filter_level(log::LevelFilter::Trace)
+            .is_test(true)
+            .try_init();
+    }
+
+    #[test]
+    fn parse_frame() {
+        init();
+        let path = std::path::PathBuf::from("src/FrameData.bin");
+        let packet = std::fs::read(path).unwrap();
+        let message = Message::from_bytes(&packet).expect("Failed to decode message from bytes");
+        match message {
+            Message::FrameData(frame) => {
+                assert_eq!(frame.packet_size, 1990);
+                assert_eq!(frame.frame_number, 169383987);
+                assert_eq!(frame.markerset_count, 6);
+                assert_eq!(frame.markerset_bytes, 1678);
+                assert_eq!(frame.unlabeled_marker_count, 0);
+                assert_eq!(frame.unlabeled_marker_bytes, 0);
+                assert_eq!(frame.rigid_body_count, 5);
+                assert_eq!(frame.rigid_body_bytes, 190);
+                assert_eq!(frame.skeleton_count, 0);
+                assert_eq!(frame.skeleton_bytes, 0);
+                assert_eq!(frame.labeled_marker_count, 0);
+                assert_eq!(frame.labeled_marker_bytes, 0);
+                assert_eq!(frame.device_count, 0);
+                assert_eq!(frame.device_bytes, 0);
+                assert_eq!(frame.device_count, 0);
+                assert_eq!(frame.device_bytes, 0);
+                assert!((frame.stamps.timestamp - 1411533.23).abs() < 1e-2);
+                assert_eq!(frame.stamps.timestamp_mid, 25742528329784);
+                assert_eq!(frame.stamps.timestamp_recv, 25742528388661);
+                assert_eq!(frame.stamps.timestamp_tx, 25742528459914);
+                assert_eq!(frame.stamps.timestamp_precision, 0);
+                assert_eq!(frame.stamps.timestamp_precision_fraction, 0);
+                assert_eq!(frame.frame_parameters.param, 0);
+                assert!(!frame.frame_parameters.is_recording);
+                assert!(!frame.frame_parameters.tracked_models_changed);
+            }
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+    }
+
+    #[test]
+    fn frame_to_packet() {
+        init();
+        let frame = FrameData {
+            frame_number: 42,
+            rigid_body_count: 1,
+            rigid_body_bytes: 38,
+            rigid_bodies: vec![RigidBody {
+                id: 3,
+                pos: glam::vec3(1.0, 2.0, 3.0),
+                rot: Quat::IDENTITY,
+                is_tracking_valid: true,
+                mean_marker_err: 0.5,
+            }],
+            ..Default::default()
+        };
+        let packet = frame.to_packet().expect("Failed to encode frame");
+        assert_eq!(Message::peek_id(&packet), Some(MessageId::FrameData));
+        let message = Message::from_bytes(&packet).expect("Failed to decode message from bytes");
+        match message {
+            Message::FrameData(decoded) => {
+                assert_eq!(decoded.packet_size as usize, packet.len() - 4);
+                let expected = FrameData {
+                    packet_size: decoded.packet_size,
+                    ..frame
+                };
+                assert_eq!(*decoded, expected);
+            }
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+    }
+
+    #[test]
+    fn framer_resync() {
+        init();
+        let frame = FrameData {
+            frame_number: 7,
+            ..Default::default()
+        };
+        let mut framer = MessageFramer::new();
+        framer.extend_from_slice(&[0xff; 9]);
+        framer.extend_from_slice(&frame.to_packet().unwrap());
+        assert!(matches!(framer.next_message(), Some(Err(_))));
+        assert_eq!(framer.resync(), 9);
+        match framer.next_message() {
+            Some(Ok(Message::FrameData(decoded))) => assert_eq!(decoded.frame_number, 7),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        assert_eq!(framer.buffered(), 0);
+        assert!(framer.next_message().is_none());
+    }
+
+    #[test]
+    fn framer_corrupt_frame_data() {
+        init();
+        let frame = FrameData {
+            frame_number: 8,
+            ..Default::default()
+        };
+        let mut framer = MessageFramer::new();
+        // a FrameData header whose payload is too short for any section
+        framer.extend_from_slice(&[7, 0, 10, 0]);
+        framer.extend_from_slice(&[0; 10]);
+        framer.extend_from_slice(&frame.to_packet().unwrap());
+        assert!(matches!(framer.next_message(), Some(Err(_))));
+        framer.resync();
+        loop {
+            match framer.next_message() {
+                Some(Ok(Message::FrameData(decoded))) => {
+                    assert_eq!(decoded.frame_number, 8);
+                    break;
+                }
+                Some(Err(_)) => {
+                    framer.resync();
+                }
+                // the zeroed payload can resync onto spurious empty messages
+                Some(Ok(_)) => {}
+                None => panic!("Expected FrameData"),
+            }
+        }
+        assert_eq!(framer.buffered(), 0);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_roundtrip() {
+        init();
+        let mut frame = *Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        frame.labeled_marker_positions = vec![labeled_marker(
+            7,
+            0.01,
+            LabeledMarkerStatus::PointCloudSolved,
+        )];
+        frame.precision_transmit_timestamp = Some(42);
+        let encoded = frame.to_postcard();
+        assert!(encoded.len() < SAMPLE_FRAME_DATA.len());
+        assert_eq!(FrameData::from_postcard(&encoded).unwrap(), frame);
+        assert!(FrameData::from_postcard(&encoded[..encoded.len() / 2]).is_err());
+    }
+
+    #[cfg(feature = "ros")]
+    #[test]
+    fn rigid_body_to_ros_pose() {
+        // ENU: Motive's +X is east, -Z north and +Y up, and a quarter turn about Motive's up
+        // axis is a quarter turn about the ROS up axis
+        let rot = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let pose = rigid_body(1, glam::vec3(1.0, 2.0, 3.0), rot).to_ros_pose();
+        assert_eq!(pose.position, [1.0, -3.0, 2.0]);
+        let expected = glam::DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2);
+        let orientation = glam::DQuat::from_array(pose.orientation);
+        assert!(orientation.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[cfg(feature = "c3d")]
+    #[test]
+    fn c3d_writer_header() {
+        init();
+        let frames: Vec<FrameData> = (0..3)
+            .map(|i| {
+                let mut first = labeled_marker(1, 0.01, LabeledMarkerStatus::PointCloudSolved);
+                first.pos = Vec3::new(i as f32, 1.0, 2.0);
+                let mut labeled_marker_positions = vec![first];
+                // the second marker drops out of the last frame
+                if i < 2 {
+                    labeled_marker_positions.push(labeled_marker(
+                        (1 << 16) | 2,
+                        0.01,
+                        LabeledMarkerStatus::PointCloudSolved,
+                    ));
+                }
+                FrameData {
+                    frame_number: i,
+                    labeled_marker_positions,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let mut out = Vec::new();
+        C3dWriter::new(120.0).write(&frames, &mut out).unwrap();
+        assert_eq!(out.len() % 512, 0);
+
+        let word = |i: usize| u16::from_le_bytes([out[2 * i], out[2 * i + 1]]);
+        let float = |at: usize| f32::from_le_bytes(out[at..at + 4].try_into().unwrap());
+        assert_eq!(out[1], 0x50);
+        assert_eq!(word(1), 2);
+        assert_eq!((word(3), word(4)), (1, 3));
+        assert!(float(12) < 0.0);
+        assert_eq!(float(20), 120.0);
+        let params = (out[0] as usize - 1) * 512;
+        assert_eq!(out[params + 3], 84);
+
+        // frames hold two points of x, y, z, residual
+        let data = (word(8) as usize - 1) * 512;
+        assert_eq!(float(data + 32), 1.0);
+        assert_eq!(float(data + 2 * 32 + 16 + 12), -1.0);
+        assert_eq!(float(data + 2 * 32 + 12), 0.0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_frame_partial_buffers() {
+        use tokio_util::codec::Decoder as _;
+
+        init();
+        let frame = FrameData {
+            frame_number: 7,
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        let mut codec = NatNetFrame::new();
+        let mut src = BytesMut::new();
+        src.extend_from_slice(&packet[..3]);
+        assert!(matches!(codec.decode(&mut src), Ok(None)));
+        src.extend_from_slice(&packet[3..20]);
+        assert!(matches!(codec.decode(&mut src), Ok(None)));
+        src.extend_from_slice(&packet[20..]);
+        src.extend_from_slice(&packet[..2]);
+        match codec.decode(&mut src) {
+            Ok(Some(Message::FrameData(decoded))) => assert_eq!(decoded.frame_number, 7),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        assert_eq!(&src[..], &packet[..2]);
+    }
+
+    #[test]
+    fn capture_index_decode_at() {
+        init();
+        // the fixtures' packet sizes don't frame them, so they're re-encoded
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        let frame = frame.to_packet().unwrap();
+        let model = model_def(vec![ModelDefData::RigidBodyDesc {
+            size: 0,
+            data: Box::new(rigid_body_desc(5, "wand")),
+        }]);
+        let model = model.to_packet().unwrap();
+        let mut capture = frame.to_vec();
+        capture.extend_from_slice(&model);
+        capture.extend_from_slice(&frame);
+
+        let index = CaptureIndex::new(&capture).expect("Failed to index capture");
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.entries()[1].offset, frame.len());
+        assert_eq!(index.entries()[1].id, MessageId::ModelDef);
+        assert_eq!(index.message_bytes(1), Some(&model[..]));
+        let message = index
+            .decode_at(1)
+            .unwrap()
+            .expect("Failed to decode ModelDef");
+        assert!(message.into_model_def().is_some());
+        let message = index
+            .decode_at(2)
+            .unwrap()
+            .expect("Failed to decode FrameData");
+        assert_eq!(message.into_frame_data().unwrap().frame_number, 169383987);
+        assert!(index.decode_at(3).is_none());
+
+        assert!(matches!(
+            CaptureIndex::new(&capture[..capture.len() - 1]),
+            Err(DecodeError::NotEnoughBytes {
+                item: "Message",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn frame_unknown_suffix_skipped() {
+        init();
+        let frame = FrameData {
+            frame_number: 12,
+            timecode: 0x0102_0304,
+            frame_parameters: FrameParameters {
+                param: 0x01,
+                is_recording: true,
+                ..Default::default()
+            },
+            precision_transmit_timestamp: Some(99),
+            ..Default::default()
+        };
+        let mut packet = frame.to_packet().unwrap().to_vec();
+        // a newer server appending suffix fields this decoder doesn't know
+        packet.extend_from_slice(&[0xaa; 12]);
+        let packet_size = (packet.len() - 4) as u16;
+        packet[2..4].copy_from_slice(&packet_size.to_le_bytes());
+        let next = FrameData {
+            frame_number: 13,
+            ..Default::default()
+        };
+        packet.extend_from_slice(&next.to_packet().unwrap());
+
+        let mut src = BytesMut::from(&packet[..]);
+        let decoded = Message::decode(&mut src)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        assert_eq!(decoded.frame_number, 12);
+        assert_eq!(decoded.timecode, 0x0102_0304);
+        assert!(decoded.frame_parameters.is_recording);
+        assert_eq!(decoded.precision_transmit_timestamp, Some(99));
+        let decoded = Message::decode(&mut src)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        assert_eq!(decoded.frame_number, 13);
+        assert!(src.is_empty());
+
+        // without a timestamp, a 4 byte end of data tag inside the packet and then another message
+        let frame = FrameData {
+            frame_number: 14,
+            precision_transmit_timestamp: None,
+            ..Default::default()
+        };
+        let mut packet = frame.to_packet().unwrap().to_vec();
+        packet.extend_from_slice(&[0; 4]);
+        let packet_size = (packet.len() - 4) as u16;
+        packet[2..4].copy_from_slice(&packet_size.to_le_bytes());
+        packet.extend_from_slice(&Message::KeepAlive.to_bytes().unwrap());
+
+        let mut src = BytesMut::from(&packet[..]);
+        let decoded = Message::decode(&mut src)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        assert_eq!(decoded.frame_number, 14);
+        assert_eq!(decoded.precision_transmit_timestamp, None);
+        assert!(matches!(Message::decode(&mut src), Ok(Message::KeepAlive)));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn read_capture() {
+        init();
+        let mut capture = Vec::new();
+        for frame_number in 0..3 {
+            let frame = FrameData {
+                frame_number,
+                ..Default::default()
+            };
+            capture.extend_from_slice(&frame.to_packet().unwrap());
+        }
+        capture.extend_from_slice(&[11, 0, 0, 0]);
+        let path = std::env::temp_dir().join(format!("optitrack-{}.bin", std::process::id()));
+        std::fs::write(&path, &capture).unwrap();
+        let messages = read_capture_file(&path);
+        std::fs::write(&path, &capture[..capture.len() - 6]).unwrap();
+        let truncated = read_capture_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let messages = messages.expect("Failed to read capture");
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[2].as_frame_data().map(|f| f.frame_number), Some(2));
+        assert!(matches!(messages[3], Message::DisconnectByTimeout));
+        assert!(matches!(
+            truncated,
+            Err(DecodeError::NotEnoughBytes {
+                item: "Message",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn message_reader_unix_socket() {
+        use std::io::Write;
+        use std::os::unix::net::UnixStream;
+
+        init();
+        let frame = FrameData {
+            frame_number: 7,
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        let (mut relay, client) = UnixStream::pair().unwrap();
+        let writer = std::thread::spawn(move || {
+            relay.write_all(&packet[..10]).unwrap();
+            relay.write_all(&packet[10..]).unwrap();
+        });
+        let mut reader = MessageReader::new(client);
+        match reader.next() {
+            Some(Ok(Message::FrameData(decoded))) => assert_eq!(decoded.frame_number, 7),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        writer.join().unwrap();
+        assert!(reader.next().is_none());
+    }
+
+    fn labeled_marker(id: u32, size: f32, status: LabeledMarkerStatus) -> LabeledMarker {
+        LabeledMarker {
+            id,
+            pos: Vec3::ZERO,
+            size,
+            status,
+            params: status.to_bits(),
+            residual: 0.0,
+        }
+    }
+
+    #[test]
+    fn solved_marker_counts() {
+        let frame = FrameData {
+            labeled_marker_positions: vec![
+                labeled_marker(1, 0.01, LabeledMarkerStatus::PointCloudSolved),
+                labeled_marker(2, 0.01, LabeledMarkerStatus::ModelSolved),
+                labeled_marker(3, 0.01, LabeledMarkerStatus::PointCloudSolved),
+                labeled_marker(4, 0.01, LabeledMarkerStatus::Occluded),
+                labeled_marker(5, 0.01, LabeledMarkerStatus::PointCloudSolved),
+                // occluded and filled in by its model
+                LabeledMarker {
+                    params: 0x05,
+                    ..labeled_marker(6, 0.01, LabeledMarkerStatus::Unrecognized)
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(frame.point_cloud_solved_count(), 3);
+        assert_eq!(frame.model_solved_count(), 2);
+        assert_eq!(FrameData::default().model_solved_count(), 0);
+    }
+
+    #[test]
+    fn filter_labeled_markers() {
+        let frame = FrameData {
+            labeled_marker_positions: vec![
+                labeled_marker(1, 0.002, LabeledMarkerStatus::PointCloudSolved),
+                labeled_marker(2, 0.014, LabeledMarkerStatus::PointCloudSolved),
+                labeled_marker(3, 0.010, LabeledMarkerStatus::Occluded),
+                labeled_marker(4, 0.012, LabeledMarkerStatus::ModelSolved),
+            ],
+            ..Default::default()
+        };
+        let ids: Vec<u32> = frame
+            .labeled_markers_larger_than(0.005)
+            .filter(|m| !m.is_occluded())
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, vec![2, 4]);
+        assert!(frame.labeled_marker_positions[2].is_occluded());
+    }
+
+    #[test]
+    fn frame_ring_drops_oldest() {
+        let mut ring = FrameRing::new(3);
+        for frame_number in 0..5 {
+            ring.push(FrameData {
+                frame_number,
+                ..Default::default()
+            });
+        }
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.dropped(), 2);
+        assert_eq!(ring.latest().map(|f| f.frame_number), Some(4));
+        let frame_numbers: Vec<u32> = ring.drain().map(|f| f.frame_number).collect();
+        assert_eq!(frame_numbers, vec![2, 3, 4]);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn convert_coords_in_place() {
+        let rot = Quat::from_rotation_y(0.5);
+        let frame = FrameData {
+            markersets: vec![MarkerSet {
+                name: "wand".into(),
+                marker_count: 1,
+                positions: vec![glam::vec3(1.0, 2.0, 3.0)],
+            }],
+            rigid_bodies: vec![
+                rigid_body(1, glam::vec3(1.0, 2.0, 3.0), rot),
+                rigid_body(2, glam::vec3(-4.0, 5.0, 0.5), Quat::IDENTITY),
+            ],
+            ..Default::default()
+        };
+        let mut converted = frame.clone();
+        let markersets_ptr = converted.markersets.as_ptr();
+        let rigid_bodies_ptr = converted.rigid_bodies.as_ptr();
+        converted.convert_coords_in_place(CoordConversion::RubToFrd);
+        assert_eq!(converted.markersets.as_ptr(), markersets_ptr);
+        assert_eq!(converted.rigid_bodies.as_ptr(), rigid_bodies_ptr);
+
+        let expected: Vec<RigidBody> = frame
+            .rigid_bodies
+            .iter()
+            .cloned()
+            .map(|body| RigidBody {
+                rot: CoordConversion::RubToFrd.apply_quat(body.rot),
+                ..body.rub_to_frd()
+            })
+            .collect();
+        assert_eq!(converted.rigid_bodies, expected);
+        // rub_to_frd leaves the orientation alone
+        assert_eq!(frame.rigid_bodies[0].clone().rub_to_frd().rot, rot);
+        assert_eq!(converted.rigid_bodies[0].pos, glam::vec3(1.0, 3.0, -2.0));
+        assert_eq!(
+            converted.markersets[0].positions,
+            vec![glam::vec3(1.0, 3.0, -2.0)]
+        );
+        // rotating the remapped offset agrees with remapping the rotated offset
+        let offset = glam::vec3(0.0, 0.0, 1.0);
+        let remapped = converted.rigid_bodies[0].rot * CoordConversion::RubToFrd.apply_vec3(offset);
+        assert!(remapped.abs_diff_eq(CoordConversion::RubToFrd.apply_vec3(rot * offset), 1e-6));
+
+        let mut unchanged = frame.clone();
+        unchanged.convert_coords_in_place(CoordConversion::Identity);
+        assert_eq!(unchanged, frame);
+    }
+
+    #[test]
+    fn timestamp_range_skips_default_stamps() {
+        let frames: Vec<FrameData> = [3.0, 0.0, 1.0]
+            .into_iter()
+            .map(|timestamp| FrameData {
+                stamps: Stamps {
+                    timestamp,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(timestamp_range(&frames), Some((1.0, 3.0)));
+        assert_eq!(timestamp_range(&frames[1..2]), None);
+        assert_eq!(timestamp_range(&[]), None);
+    }
+
+    #[test]
+    fn streaming_ids_tagged() {
+        let frame = FrameData {
+            rigid_bodies: vec![
+                rigid_body(3, Vec3::ZERO, Quat::IDENTITY),
+                rigid_body(4, Vec3::ZERO, Quat::IDENTITY),
+            ],
+            skeletons: vec![Skeleton {
+                id: 3,
+                rigid_body_count: 0,
+                rigid_bodies: Vec::new(),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            frame.all_ids(),
+            vec![
+                StreamingId::RigidBody(3),
+                StreamingId::RigidBody(4),
+                StreamingId::Skeleton(3),
+            ]
+        );
+        assert_eq!(frame.id_collisions(), vec![3]);
+        assert!(FrameData::default().id_collisions().is_empty());
+    }
+
+    #[test]
+    fn content_digest() {
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        let digest = frame.content_digest();
+        assert_eq!(frame.clone().content_digest(), digest);
+
+        let mut restamped = frame.clone();
+        restamped.timecode += 1;
+        restamped.stamps.timestamp += 1.0;
+        assert_eq!(restamped.content_digest(), digest);
+
+        let mut moved = frame.clone();
+        moved.markersets[0].positions[0].x += 0.001;
+        assert_ne!(moved.content_digest(), digest);
+    }
+
+    #[test]
+    fn all_rigid_bodies_includes_bones() {
+        let frame = FrameData {
+            rigid_bodies: vec![rigid_body(1, Vec3::ZERO, Quat::IDENTITY)],
+            skeletons: vec![Skeleton {
+                id: 7,
+                rigid_body_count: 2,
+                rigid_bodies: vec![
+                    rigid_body(7 << 16 | 1, Vec3::ZERO, Quat::IDENTITY),
+                    rigid_body(7 << 16 | 2, Vec3::X, Quat::IDENTITY),
+                ],
+            }],
+            ..Default::default()
+        };
+        let ids: Vec<u32> = frame.all_rigid_bodies().map(|rb| rb.id).collect();
+        assert_eq!(ids, vec![1, 7 << 16 | 1, 7 << 16 | 2]);
+    }
+
+    #[test]
+    fn marker_swap_detected() {
+        let frame = |markers: [(u32, Vec3); 3]| FrameData {
+            labeled_marker_positions: markers
+                .into_iter()
+                .map(|(id, pos)| LabeledMarker {
+                    pos,
+                    ..labeled_marker(id, 0.01, LabeledMarkerStatus::ModelSolved)
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut detector = MarkerSwapDetector::default();
+        let first = frame([(1, Vec3::ZERO), (2, Vec3::X), (3, Vec3::Y)]);
+        assert!(detector.feed(&first).is_empty());
+        // 1 and 2 traded places while 3 drifted slightly
+        let second = frame([
+            (1, glam::vec3(1.01, 0.0, 0.0)),
+            (2, glam::vec3(0.01, 0.0, 0.0)),
+            (3, glam::vec3(0.0, 1.01, 0.0)),
+        ]);
+        assert_eq!(
+            detector.feed(&second),
+            vec![MarkerSwap {
+                first: 1,
+                second: 2
+            }]
+        );
+        assert!(detector.feed(&second).is_empty());
+    }
+
+    #[test]
+    fn frames_since_wraps() {
+        let frame = FrameData {
+            frame_number: 1,
+            ..Default::default()
+        };
+        assert_eq!(frame.frames_since(u32::MAX - 1), 3);
+        assert_eq!(frame.frames_since(0), 1);
+        assert_eq!(frame.frames_since(1), 0);
+    }
+
+    #[test]
+    fn frame_dedup_drops_repeats() {
+        let mut dedup = FrameDedup::new(4);
+        let frames = [1, 2, 2, 3].map(|frame_number| FrameData {
+            frame_number,
+            ..Default::default()
+        });
+        let passed: Vec<u32> = frames
+            .iter()
+            .filter(|frame| dedup.accept(frame))
+            .map(FrameData::frame_id)
+            .collect();
+        assert_eq!(passed, vec![1, 2, 3]);
+
+        // numbering wraps around
+        let mut dedup = FrameDedup::new(4);
+        assert!(dedup.check(u32::MAX));
+        assert!(dedup.check(0));
+        assert!(!dedup.check(u32::MAX));
+        assert!(dedup.check(5));
+
+        // a jump back of the window or more is a restart, e.g. a playback loop
+        let mut dedup = FrameDedup::new(4);
+        for frame_id in [169383986, 169383987] {
+            assert!(dedup.check(frame_id));
+        }
+        assert!(dedup.check(0));
+        assert!(dedup.check(1));
+        assert!(!dedup.check(0));
+        assert!(!dedup.check(1));
+        assert!(dedup.check(2));
+    }
+
+    #[test]
+    fn truncated_rigid_body_desc() {
+        init();
+        let mut src = BytesMut::new();
+        src.extend_from_slice(b"rb\0");
+        src.put_i32_le(1);
+        src.put_i32_le(-1);
+        src.put_f32_le(0.0);
+        src.put_f32_le(0.0);
+        src.put_f32_le(0.0);
+        src.put_i32_le(3);
+        src.extend_from_slice(&[0; 10]);
+        let err = RigidBodyDescCodec::default()
+            .decode(&mut src)
+            .expect_err("Decoded a truncated RigidBodyDesc");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NotEnoughBytes {
+                item: "RigidBodyDesc markers",
+                expected: 48,
+                got: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn labeled_marker_status_bits() {
+        for status in [
+            LabeledMarkerStatus::Occluded,
+            LabeledMarkerStatus::PointCloudSolved,
+            LabeledMarkerStatus::ModelSolved,
+            LabeledMarkerStatus::Unrecognized,
+        ] {
+            assert_eq!(LabeledMarkerStatus::from_bits(status.to_bits()), status);
+        }
+
+        // an occluded marker filled in by its model sets two status bits
+        let marker = LabeledMarker {
+            params: 0x05,
+            ..labeled_marker(1, 0.01, LabeledMarkerStatus::Unrecognized)
+        };
+        assert!(marker.is_occluded());
+        assert!(marker.is_model_solved());
+        assert!(!marker.is_point_cloud_solved());
+        let mut dst = BytesMut::new();
+        LabeledMarkerCodec::default()
+            .encode(marker.clone(), &mut dst)
+            .unwrap();
+        let decoded = LabeledMarkerCodec::default().decode(&mut dst).unwrap();
+        assert_eq!(decoded.status, LabeledMarkerStatus::Unrecognized);
+        assert_eq!(decoded, marker);
+    }
+
+    #[test]
+    fn active_marker_flag() {
+        init();
+        let marker = LabeledMarker {
+            params: ACTIVE_MARKER_FLAG | LabeledMarkerStatus::ModelSolved.to_bits(),
+            ..labeled_marker(5 << 16 | 1, 0.01, LabeledMarkerStatus::ModelSolved)
+        };
+        assert!(marker.is_active_marker());
+        assert!(!labeled_marker(1, 0.01, LabeledMarkerStatus::ModelSolved).is_active_marker());
+
+        let mut dst = BytesMut::new();
+        LabeledMarkerCodec::default()
+            .encode(marker.clone(), &mut dst)
+            .unwrap();
+        let decoded = LabeledMarkerCodec::default().decode(&mut dst).unwrap();
+        assert_eq!(decoded.status, LabeledMarkerStatus::ModelSolved);
+        assert!(decoded.is_active_marker());
+        assert_eq!(decoded, marker);
+    }
+
+    #[test]
+    fn frame_without_stamps() {
+        init();
+        let frame = FrameData {
+            frame_number: 11,
+            timecode: 5,
+            timecode_sub: 6,
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        // stamps are 40 bytes and the suffix is 2 bytes
+        let mut src = BytesMut::from(&packet[2..packet.len() - 42]);
+        let decoded = FrameDataCodec::default()
+            .decode(&mut src)
+            .expect("Failed to decode frame");
+        assert_eq!(decoded.timecode_sub, 6);
+        assert_eq!(decoded.stamps, Stamps::default());
+        assert_eq!(decoded.frame_parameters, FrameParameters::default());
+        assert!(src.is_empty());
+
+        let mut src = BytesMut::from(&packet[2..packet.len() - 41]);
+        assert!(FrameDataCodec::default().decode(&mut src).is_err());
+    }
+
+    fn server_info_packet(natnet_version: [u8; 4]) -> Vec<u8> {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&u16::from(MessageId::PingResponse).to_le_bytes());
+        packet.extend_from_slice(&264_i16.to_le_bytes());
+        let mut app_name = [0u8; 256];
+        app_name[..6].copy_from_slice(b"Motive");
+        packet.extend_from_slice(&app_name);
+        packet.extend_from_slice(&[3, 1, 0, 0]);
+        packet.extend_from_slice(&natnet_version);
+        packet
+    }
+
+    #[test]
+    fn decode_response_payloads() {
+        init();
+        let response = |payload: &[u8]| {
+            let mut packet = Vec::new();
+            packet.extend_from_slice(&u16::from(MessageId::Response).to_le_bytes());
+            packet.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+            packet.extend_from_slice(payload);
+            match Message::from_bytes(&packet).unwrap() {
+                Message::Response(response) => *response,
+                val => panic!("Expected Response, got {:?}", val),
+            }
+        };
+        assert!(matches!(response(&42_i32.to_le_bytes()), Response::Int(42)));
+        assert!(matches!(
+            response(&(-7_i32).to_le_bytes()),
+            Response::Int(-7)
+        ));
+        assert!(matches!(response(&1.5_f32.to_le_bytes()), Response::Float(v) if v == 1.5));
+        assert!(matches!(response(b"OK\0"), Response::Text(text) if text == "OK\0"));
+
+        let mut packet = server_info_packet([4, 1, 0, 0]);
+        packet[..2].copy_from_slice(&u16::from(MessageId::Response).to_le_bytes());
+        match Message::from_bytes(&packet).unwrap() {
+            Message::Response(response) => match *response {
+                Response::ServerInfo(info) => assert_eq!(info.natnet_version, [4, 1, 0, 0]),
+                val => panic!("Expected ServerInfo, got {:?}", val),
+            },
+            val => panic!("Expected Response, got {:?}", val),
+        }
+    }
+
+    struct MockServer {
+        socket: UdpSocket,
+        natnet_version: [u8; 4],
+    }
+
+    impl MockServer {
+        fn new(natnet_version: [u8; 4]) -> Self {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            Self {
+                socket,
+                natnet_version,
+            }
+        }
+
+        fn config(&self) -> NatNetConfig {
+            NatNetConfig {
+                server_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                local_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                command_port: self.socket.local_addr().unwrap().port(),
+                transport: TransportMode::Unicast,
+                ..Default::default()
+            }
+        }
+
+        // Answers the connect request and returns the client data socket address
+        fn accept(&self) -> SocketAddr {
+            let mut buf = [0u8; 1024];
+            let (_, client) = self.socket.recv_from(&mut buf).unwrap();
+            assert_eq!(Message::peek_id(&buf), Some(MessageId::Ping));
+            self.socket
+                .send_to(&server_info_packet(self.natnet_version), client)
+                .unwrap();
+            let (_, data_client) = self.socket.recv_from(&mut buf).unwrap();
+            assert_eq!(Message::peek_id(&buf), Some(MessageId::KeepAlive));
+            data_client
+        }
+    }
+
+    #[test]
+    fn client_connect() {
+        init();
+        let server = MockServer::new([3, 1, 0, 0]);
+        let config = server.config();
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            let frame = FrameData {
+                frame_number: 99,
+                ..Default::default()
+            };
+            server
+                .socket
+                .send_to(&frame.to_packet().unwrap(), data_client)
+                .unwrap();
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        assert_eq!(client.codec().version, NatNetVersion::new(3, 1, 0, 0));
+        assert_eq!(
+            client.server_info().app_name.trim_end_matches('\0'),
+            "Motive"
+        );
+        match client.recv().expect("Failed to receive frame") {
+            Message::FrameData(frame) => assert_eq!(frame.frame_number, 99),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        mock.join().unwrap();
+    }
+
+    #[test]
+    fn client_multicast_v6() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let group: Ipv6Addr = "ff01::4e4e".parse().unwrap();
+        let config = NatNetConfig {
+            data_port: 0,
+            transport: TransportMode::MulticastV6 {
+                group,
+                interface_index: 0,
+            },
+            read_timeout: Some(Duration::from_secs(5)),
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            let (_, client) = server.socket.recv_from(&mut buf).unwrap();
+            server
+                .socket
+                .send_to(&server_info_packet(server.natnet_version), client)
+                .unwrap();
+        });
+        let client = NatNetClient::connect(config);
+        mock.join().unwrap();
+        let mut client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("Skipping, IPv6 multicast unsupported: {}", e);
+                return;
+            }
+        };
+        let port = client.data_socket.local_addr().unwrap().port();
+        let sender = UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).unwrap();
+        let frame = FrameData {
+            frame_number: 66,
+            ..Default::default()
+        };
+        if let Err(e) = sender.send_to(&frame.to_packet().unwrap(), (group, port)) {
+            log::warn!("Skipping, IPv6 multicast unsupported: {}", e);
+            return;
+        }
+        match client.recv().expect("Failed to receive frame") {
+            Message::FrameData(frame) => assert_eq!(frame.frame_number, 66),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+    }
+
+    fn marker_set(name: &str, x: f32) -> MarkerSet {
+        MarkerSet {
+            name: name.to_string(),
+            marker_count: 1,
+            positions: vec![Vec3::new(x, 0.0, 0.0)],
+        }
+    }
+
+    #[test]
+    fn client_markerset_stream() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = NatNetConfig {
+            read_timeout: Some(Duration::from_millis(200)),
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            for (frame_number, names) in [
+                (1, vec!["Wand"]),
+                (2, vec!["bat"]),
+                (3, vec!["bat", "Wand"]),
+            ] {
+                let frame = FrameData {
+                    frame_number,
+                    markerset_count: names.len() as u32,
+                    markersets: names
+                        .into_iter()
+                        .map(|name| marker_set(name, frame_number as f32))
+                        .collect(),
+                    ..Default::default()
+                };
+                server
+                    .socket
+                    .send_to(&frame.to_packet().unwrap(), data_client)
+                    .unwrap();
+            }
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        let mut stream = client.markerset_stream("Wand");
+        let markersets: Vec<MarkerSet> = stream.by_ref().take(2).map(|ms| ms.unwrap()).collect();
+        assert_eq!(
+            markersets,
+            vec![marker_set("Wand", 1.0), marker_set("Wand", 3.0)]
+        );
+        let err = stream.next().unwrap().expect_err("Expected a timeout");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::Timeout)
+        );
+        mock.join().unwrap();
+    }
+
+    #[test]
+    fn client_frames_route_control_messages() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = NatNetConfig {
+            read_timeout: Some(Duration::from_millis(200)),
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            let frame = FrameData {
+                frame_number: 7,
+                ..Default::default()
+            };
+            let server_info = server_info_packet([4, 1, 0, 0]);
+            for packet in [&server_info[..], &frame.to_packet().unwrap()[..]] {
+                server.socket.send_to(packet, data_client).unwrap();
+            }
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        assert!(client.poll_control().is_none());
+        let frame = client
+            .frames()
+            .next()
+            .unwrap()
+            .expect("Failed to receive frame");
+        assert_eq!(frame.frame_number, 7);
+        match client.poll_control() {
+            Some(Message::PingResponse(server_info)) => {
+                assert_eq!(server_info.natnet_version, [4, 1, 0, 0])
+            }
+            val => panic!("Expected PingResponse, got {:?}", val),
+        }
+        assert!(client.poll_control().is_none());
+        mock.join().unwrap();
+    }
+
+    #[test]
+    fn client_spawn_reader() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = NatNetConfig {
+            read_timeout: Some(Duration::from_millis(100)),
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            for frame_number in 1..=3 {
+                let frame = FrameData {
+                    frame_number,
+                    ..Default::default()
+                };
+                server
+                    .socket
+                    .send_to(&frame.to_packet().unwrap(), data_client)
+                    .unwrap();
+            }
+        });
+        let client = NatNetClient::connect(config).expect("Failed to connect");
+        let (reader, frames) = client.spawn_reader();
+        let frame_numbers: Vec<u32> = frames
+            .iter()
+            .take(3)
+            .map(|frame| frame.expect("Failed to receive frame").frame_number)
+            .collect();
+        assert_eq!(frame_numbers, vec![1, 2, 3]);
+        assert_eq!(frames.recv().unwrap(), Err(DecodeError::Timeout));
+        // the next timeout finds the receiver gone and stops the reader
+        drop(frames);
+        reader.join().unwrap();
+        mock.join().unwrap();
+    }
+
+    fn rigid_body(id: u32, pos: Vec3, rot: Quat) -> RigidBody {
+        RigidBody {
+            id,
+            pos,
+            rot,
+            is_tracking_valid: true,
+            mean_marker_err: 0.0,
+        }
+    }
+
+    fn rigid_body_desc(id: i32, name: &str) -> RigidBodyDesc {
+        RigidBodyDesc {
+            name: name.to_string(),
+            id,
+            parent_id: -1,
+            pos: Vec3::ZERO,
+            marker_count: 0,
+            marker_offsets: Vec::new(),
+            marker_active_labels: Vec::new(),
+            marker_names: Vec::new(),
+        }
+    }
+
+    fn model_def(dataset: Vec<ModelDefData>) -> ModelDef {
+        ModelDef {
+            packet_size: 0,
+            dataset_count: dataset.len() as u32,
+            dataset,
+        }
+    }
+
+    #[test]
+    fn model_def_skips_undecoded_descriptions() {
+        init();
+        let packet = model_def(vec![ModelDefData::RigidBodyDesc {
+            size: 0,
+            data: Box::new(rigid_body_desc(1, "wand")),
+        }])
+        .to_packet()
+        .unwrap();
+        // a force plate description ahead of the rigid body
+        let mut src = BytesMut::from(&packet[2..4]);
+        src.put_u32_le(2);
+        src.put_u32_le(3);
+        src.put_u32_le(12);
+        src.extend_from_slice(&[0xAB; 12]);
+        src.extend_from_slice(&packet[8..]);
+        let model = ModelDefCodec::default()
+            .decode(&mut src.clone())
+            .expect("Failed to decode ModelDef");
+        assert!(matches!(model.dataset[0], ModelDefData::ForcePlateDesc));
+        assert_eq!(model.rigid_body_desc(1).unwrap().name, "wand");
+
+        // without element sizes there's no way past it
+        let mut src = BytesMut::from(&[0, 0, 1, 0, 0, 0, 3, 0, 0, 0][..]);
+        let err = ModelDefCodec::new(NatNetVersion::new(4, 0, 0, 0))
+            .decode(&mut src)
+            .expect_err("Skipped a ForcePlateDesc without a size");
+        assert!(matches!(
+            err.downcast_ref::<DecodeError>(),
+            Some(DecodeError::Invalid { .. })
+        ));
+
+        // a truncated element header
+        let mut src = BytesMut::from(&[0, 0, 1, 0, 0, 0, 3, 0][..]);
+        let err = ModelDefCodec::default()
+            .decode(&mut src)
+            .expect_err("Decoded a truncated ModelDef");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NotEnoughBytes {
+                item: "ModelDef data header",
+                expected: 8,
+                got: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_model_def() {
+        init();
+        let packet = model_def(vec![]).to_packet().unwrap();
+        assert_eq!(packet.len(), 8);
+
+        let decoded = ModelDefCodec::default()
+            .decode(&mut BytesMut::from(&packet[2..]))
+            .expect("Failed to decode empty model");
+        assert_eq!(decoded.dataset_count, 0);
+        assert!(decoded.dataset.is_empty());
+
+        let message = Message::from_bytes(&packet).unwrap();
+        let decoded = message.into_model_def().expect("Expected ModelDef");
+        assert_eq!(decoded.packet_size, 4);
+        assert!(decoded.dataset.is_empty());
+
+        // a header cut short is an error rather than a panic
+        assert!(ModelDefCodec::default()
+            .decode(&mut BytesMut::from(&packet[2..6]))
+            .is_err());
+    }
+
+    #[test]
+    fn frame_with_names() {
+        let model = model_def(vec![ModelDefData::RigidBodyDesc {
+            size: 0,
+            data: Box::new(rigid_body_desc(5, "wand")),
+        }]);
+        let frame = FrameData {
+            rigid_body_count: 2,
+            rigid_bodies: vec![
+                rigid_body(5, Vec3::ZERO, Quat::IDENTITY),
+                rigid_body(9, Vec3::ZERO, Quat::IDENTITY),
+            ],
+            ..Default::default()
+        };
+        let named: Vec<(&str, u32)> = frame
+            .with_names(&model)
+            .into_iter()
+            .map(|(name, rb)| (name, rb.id))
+            .collect();
+        assert_eq!(named, vec![("wand", 5), ("", 9)]);
+    }
+
+    #[test]
+    fn assets_with_names() {
+        let model = model_def(vec![ModelDefData::AssetDesc {
+            size: 0,
+            data: Box::new(AssetDesc {
+                name: "glove".into(),
+                asset_type: 1,
+                id: 3,
+                descriptions: Bytes::new(),
+            }),
+        }]);
+        let packet = model.to_packet().unwrap();
+        let model = Message::from_bytes(&packet)
+            .unwrap()
+            .into_model_def()
+            .expect("Expected ModelDef");
+        let frame = FrameData {
+            asset_count: 2,
+            assets: vec![
+                Asset {
+                    id: 3,
+                    rigid_body_count: 0,
+                    rigid_bodies: vec![],
+                    markers: MarkerAsset::default(),
+                },
+                Asset {
+                    id: 4,
+                    rigid_body_count: 0,
+                    rigid_bodies: vec![],
+                    markers: MarkerAsset::default(),
+                },
+            ],
+            ..Default::default()
+        };
+        let named: Vec<(&str, u32)> = frame
+            .assets_with_names(&model)
+            .into_iter()
+            .map(|(name, asset)| (name, asset.id))
+            .collect();
+        assert_eq!(named, vec![("glove", 3), ("", 4)]);
+    }
+
+    #[test]
+    fn asset_desc_roundtrip() {
+        init();
+        // one rigid body description and no marker descriptions, left undecoded
+        let mut descriptions = BytesMut::new();
+        descriptions.put_i32_le(1);
+        descriptions.extend_from_slice(&[0xAB; 20]);
+        descriptions.put_i32_le(0);
+        let desc = AssetDesc {
+            name: "glove".into(),
+            asset_type: 1,
+            id: 3,
+            descriptions: descriptions.freeze(),
+        };
+        let model = model_def(vec![ModelDefData::AssetDesc {
+            size: 0,
+            data: Box::new(desc.clone()),
+        }]);
+        let packet = model.to_packet().unwrap();
+        let decoded = Message::from_bytes(&packet)
+            .unwrap()
+            .into_model_def()
+            .expect("Expected ModelDef");
+        assert_eq!(decoded.asset_desc(3), Some(&desc));
+        assert_eq!(decoded.to_packet().unwrap(), packet);
+    }
+
+    #[test]
+    fn validate_frame_against_model() {
+        let mut desc = MarkerSetDesc::new("wand", 3);
+        desc.marker_names = vec!["tip".into(), "mid".into(), "base".into()];
+        let model = model_def(vec![
+            ModelDefData::RigidBodyDesc {
+                size: 0,
+                data: Box::new(rigid_body_desc(5, "wand")),
+            },
+            ModelDefData::MarkerSetDesc {
+                size: 0,
+                data: Box::new(desc),
+            },
+        ]);
+        let mut wand = MarkerSet::new("wand", 2);
+        wand.positions = vec![Vec3::X, Vec3::Y];
+        let mut frame = FrameData {
+            rigid_body_count: 1,
+            rigid_bodies: vec![rigid_body(5, Vec3::ZERO, Quat::IDENTITY)],
+            markerset_count: 1,
+            markersets: vec![wand],
+            ..Default::default()
+        };
+        assert_eq!(
+            frame.validate_against(&model),
+            vec![ValidationWarning::MarkerCountMismatch {
+                name: "wand".into(),
+                expected: 3,
+                got: 2,
+            }]
+        );
+
+        frame.markersets[0].positions.push(Vec3::Z);
+        frame
+            .rigid_bodies
+            .push(rigid_body(9, Vec3::ZERO, Quat::IDENTITY));
+        frame.markersets.push(MarkerSet::new("bat", 0));
+        assert_eq!(
+            frame.validate_against(&model),
+            vec![
+                ValidationWarning::UnknownRigidBody { id: 9 },
+                ValidationWarning::UnknownMarkerSet { name: "bat".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_f64() {
+        let widen = |v: Vec3| DVec3::new(v.x as f64, v.y as f64, v.z as f64);
+        let mut marker = labeled_marker(1, 0.01, LabeledMarkerStatus::ModelSolved);
+        marker.pos = glam::vec3(0.1, -2.7, 0.3);
+        let unlabeled = glam::vec3(1.3, 0.0, -0.3);
+        let body = rigid_body(5, glam::vec3(0.7, 1.9, -3.3), Quat::IDENTITY);
+        let frame = FrameData {
+            unlabeled_marker_positions: vec![unlabeled],
+            labeled_marker_positions: vec![marker.clone()],
+            rigid_bodies: vec![body.clone()],
+            ..Default::default()
+        };
+        let widened: Vec<_> = frame.positions_f64().collect();
+        assert_eq!(
+            widened,
+            vec![(Some(1), widen(marker.pos)), (None, widen(unlabeled))]
+        );
+        let bodies: Vec<_> = frame.rigid_body_positions_f64().collect();
+        assert_eq!(bodies, vec![(5, widen(body.pos))]);
+    }
+
+    #[test]
+    fn named_markersets() {
+        let frame = FrameData {
+            markerset_count: 2,
+            markersets: vec![MarkerSet::new("all", 0), MarkerSet::new("wand", 0)],
+            ..Default::default()
+        };
+        assert!(frame.markersets[0].is_aggregate());
+        assert!(!frame.markersets[1].is_aggregate());
+        let names: Vec<&str> = frame
+            .named_markersets()
+            .map(|ms| ms.name.as_str())
+            .collect();
+        assert_eq!(names, ["wand"]);
+    }
+
+    #[test]
+    fn centroids() {
+        let mut markerset = MarkerSet::new("wand", 3);
+        assert_eq!(markerset.centroid(), None);
+        markerset.positions = vec![
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 2.0, 0.0),
+            glam::vec3(2.0, 1.0, 3.0),
+        ];
+        assert_eq!(markerset.centroid(), Some(glam::vec3(1.0, 1.0, 1.0)));
+
+        let frame = FrameData {
+            unlabeled_marker_positions: vec![Vec3::ZERO, glam::vec3(2.0, -4.0, 6.0)],
+            ..Default::default()
+        };
+        assert_eq!(frame.unlabeled_centroid(), Some(glam::vec3(1.0, -2.0, 3.0)));
+        assert_eq!(FrameData::default().unlabeled_centroid(), None);
+    }
+
+    #[test]
+    fn markerset_occlusion() {
+        let mut markerset = MarkerSet::new("wand", 3);
+        assert_eq!(markerset.occlusion_rate(), 0.0);
+        markerset.positions = vec![
+            glam::vec3(1.0, 0.0, 0.0),
+            Vec3::ZERO,
+            glam::vec3(2.0, 1.0, 3.0),
+        ];
+        assert_eq!(markerset.occluded_count(), 1);
+        assert_eq!(markerset.occlusion_rate(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn labeled_markers_by_model() {
+        let frame = FrameData {
+            labeled_marker_positions: vec![
+                labeled_marker(5 << 16 | 1, 0.01, LabeledMarkerStatus::ModelSolved),
+                labeled_marker(6 << 16 | 1, 0.01, LabeledMarkerStatus::ModelSolved),
+                labeled_marker(5 << 16 | 2, 0.01, LabeledMarkerStatus::ModelSolved),
+            ],
+            ..Default::default()
+        };
+        let models = frame.labeled_markers_by_model();
+        assert_eq!(models.len(), 2);
+        let marker_ids: Vec<u16> = models[&5].iter().map(|m| m.marker_id()).collect();
+        assert_eq!(marker_ids, vec![1, 2]);
+        assert_eq!(models[&6].len(), 1);
+    }
+
+    #[test]
+    fn inconsistent_markerset_section() {
+        init();
+        let frame = FrameData {
+            markerset_count: 1,
+            markersets: vec![MarkerSet::new("wand", 0)],
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        for (count, bytes) in [(1_u32, 0_u32), (0, 9), (3, 9)] {
+            let mut packet = packet.clone();
+            packet[8..12].copy_from_slice(&count.to_le_bytes());
+            packet[12..16].copy_from_slice(&bytes.to_le_bytes());
+            let err = FrameDataCodec::default()
+                .decode(&mut BytesMut::from(&packet[2..]))
+                .expect_err("Decoded an inconsistent markerset section");
+            assert_eq!(
+                err.downcast_ref::<DecodeError>(),
+                Some(&DecodeError::InconsistentSection {
+                    section: "markerset"
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn section_size_mismatch() {
+        init();
+        let frame = FrameData {
+            markerset_count: 1,
+            markersets: vec![MarkerSet {
+                name: "wand".into(),
+                marker_count: 1,
+                positions: vec![Vec3::ONE],
+            }],
+            ..Default::default()
+        };
+        let mut packet = frame.to_packet().unwrap();
+        let markerset_bytes = u32::from_le_bytes(packet[12..16].try_into().unwrap());
+        assert_eq!(markerset_bytes, 21);
+        // declare a longer section, as if the name were mis-sized
+        packet[12..16].copy_from_slice(&23_u32.to_le_bytes());
+
+        let mut codec = FrameDataCodec {
+            strict: true,
+            ..Default::default()
+        };
+        let err = codec
+            .decode(&mut BytesMut::from(&packet[2..]))
+            .expect_err("Decoded a mis-sized markerset section in strict mode");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::SectionSizeMismatch {
+                section: "MarkerSet section",
+                expected: 23,
+                got: 21,
+            })
+        );
+        let decoded = FrameDataCodec::default()
+            .decode(&mut BytesMut::from(&packet[2..]))
+            .unwrap();
+        assert_eq!(decoded.markersets, frame.markersets);
+
+        let (decoded, diagnostics) = FrameDataCodec::default()
+            .decode_with_diagnostics(&mut BytesMut::from(&packet[2..]))
+            .unwrap();
+        assert_eq!(decoded.markersets, frame.markersets);
+        assert_eq!(
+            diagnostics.warnings,
+            vec![DecodeWarning {
+                section: "MarkerSet section",
+                kind: DecodeWarningKind::SectionSizeMismatch,
+                detail: "MarkerSet section declared 23 bytes but its elements used 21".into(),
+            }]
+        );
+        let (_, diagnostics) = FrameDataCodec::default()
+            .decode_with_diagnostics(&mut BytesMut::from(&SAMPLE_FRAME_DATA[2..]))
+            .unwrap();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn strict_rejects_non_finite() {
+        init();
+        let mut src = BytesMut::new();
+        for v in [0.0, f32::NAN, 0.0, 1.0] {
+            src.put_f32_le(v);
+        }
+        let err = QuatCodec { strict: true }
+            .decode(&mut src.clone())
+            .expect_err("Decoded a NaN quaternion in strict mode");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NonFinite { field: "Quat" })
+        );
+
+        let mut packet = BytesMut::new();
+        RigidBodyCodec::default()
+            .encode(rigid_body(1, Vec3::ZERO, Quat::IDENTITY), &mut packet)
+            .unwrap();
+        // overwrite the position y component
+        packet[8..12].copy_from_slice(&f32::INFINITY.to_le_bytes());
+        let err = RigidBodyCodec { strict: true }
+            .decode(&mut packet.clone())
+            .expect_err("Decoded a non-finite position in strict mode");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NonFinite {
+                field: "RigidBody.pos"
+            })
+        );
+        assert!(RigidBodyCodec::default().decode(&mut packet).is_ok());
+
+        // a zero quaternion can't be normalized
+        let mut src = BytesMut::from(&[0u8; 16][..]);
+        let err = QuatCodec { strict: true }
+            .decode(&mut src.clone())
+            .expect_err("Decoded a zero quaternion in strict mode");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NonFinite { field: "Quat" })
+        );
+        assert_eq!(
+            QuatCodec::default().decode(&mut src).unwrap(),
+            Quat::from_xyzw(0.0, 0.0, 0.0, 0.0)
+        );
+        let mut packet = BytesMut::new();
+        RigidBodyCodec::default()
+            .encode(rigid_body(1, Vec3::ZERO, Quat::IDENTITY), &mut packet)
+            .unwrap();
+        packet[16..32].fill(0);
+        let err = RigidBodyCodec { strict: true }
+            .decode(&mut packet.clone())
+            .expect_err("Decoded a zero rotation in strict mode");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::NonFinite {
+                field: "RigidBody.rot"
+            })
+        );
+        assert!(RigidBodyCodec::default().decode(&mut packet).is_ok());
+
+        // marker positions
+        let nan = Vec3::new(0.0, f32::NAN, 0.0);
+        let frames = [
+            (
+                FrameData {
+                    markerset_count: 1,
+                    markersets: vec![MarkerSet {
+                        name: "wand".into(),
+                        marker_count: 1,
+                        positions: vec![nan],
+                    }],
+                    ..Default::default()
+                },
+                "MarkerSet.positions",
+            ),
+            (
+                FrameData {
+                    unlabeled_marker_count: 1,
+                    unlabeled_marker_positions: vec![nan],
+                    ..Default::default()
+                },
+                "UnlabeledMarker.pos",
+            ),
+            (
+                FrameData {
+                    labeled_marker_count: 1,
+                    labeled_marker_positions: vec![LabeledMarker {
+                        pos: nan,
+                        ..labeled_marker(1, 0.01, LabeledMarkerStatus::PointCloudSolved)
+                    }],
+                    ..Default::default()
+                },
+                "LabeledMarker.pos",
+            ),
+        ];
+        for (frame, field) in frames {
+            let packet = frame.to_packet().unwrap();
+            let mut codec = FrameDataCodec {
+                strict: true,
+                ..Default::default()
+            };
+            let err = codec
+                .decode(&mut BytesMut::from(&packet[2..]))
+                .expect_err("Decoded a non-finite marker in strict mode");
+            assert_eq!(
+                err.downcast_ref::<DecodeError>(),
+                Some(&DecodeError::NonFinite { field })
+            );
+            assert!(FrameDataCodec::default()
+                .decode(&mut BytesMut::from(&packet[2..]))
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn decode_leaves_trailing_bytes() {
+        init();
+        let frame = FrameData {
+            frame_number: 3,
+            ..Default::default()
+        };
+        let mut src = frame.to_packet().unwrap();
+        src.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        match Message::decode(&mut src).expect("Failed to decode message") {
+            Message::FrameData(decoded) => assert_eq!(decoded.frame_number, 3),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        assert_eq!(&src[..], &[0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn fit_pose_recovers_transform() {
+        let mut desc = rigid_body_desc(1, "wand");
+        desc.marker_offsets = vec![
+            glam::vec3(0.1, 0.0, 0.0),
+            glam::vec3(0.0, 0.2, 0.0),
+            glam::vec3(0.0, 0.0, 0.15),
+            glam::vec3(-0.05, -0.05, 0.02),
+        ];
+        desc.marker_count = 4;
+        for rot in [
+            Quat::IDENTITY,
+            Quat::from_rotation_y(std::f32::consts::PI),
+            Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.2, 2.5),
+        ] {
+            let body = rigid_body(1, glam::vec3(1.0, -2.0, 0.5), rot);
+            let observed = desc.world_marker_positions(&body);
+            let (pos, fitted) = desc.fit_pose(&observed).expect("Failed to fit pose");
+            assert!(pos.abs_diff_eq(body.pos, 1e-5));
+            assert!(fitted.dot(rot).abs() > 1.0 - 1e-6);
+        }
+        let body = rigid_body(1, Vec3::ONE, Quat::IDENTITY);
+
+        // collinear markers leave the roll about their line unconstrained
+        desc.marker_offsets = vec![Vec3::ZERO, Vec3::X, Vec3::X * 2.0];
+        let observed = desc.world_marker_positions(&body);
+        assert!(desc.fit_pose(&observed).is_none());
+        assert!(desc.fit_pose(&observed[..2]).is_none());
+    }
+
+    #[test]
+    fn world_marker_positions() {
+        let desc = RigidBodyDesc {
+            marker_count: 2,
+            marker_offsets: vec![glam::vec3(1.0, 0.0, 0.0), glam::vec3(0.0, 0.0, 2.0)],
+            ..rigid_body_desc(1, "body")
+        };
+        let body = rigid_body(1, Vec3::ZERO, Quat::IDENTITY);
+        assert_eq!(desc.world_marker_positions(&body), desc.marker_offsets);
+
+        let body = rigid_body(1, glam::vec3(1.0, 2.0, 3.0), Quat::IDENTITY);
+        assert_eq!(
+            desc.world_marker_positions(&body),
+            vec![glam::vec3(2.0, 2.0, 3.0), glam::vec3(1.0, 2.0, 5.0)]
+        );
+
+        let body = rigid_body(
+            1,
+            Vec3::ZERO,
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+        );
+        let positions = desc.world_marker_positions(&body);
+        assert!(positions[0].abs_diff_eq(glam::vec3(0.0, 0.0, -1.0), 1e-6));
+        assert!(positions[1].abs_diff_eq(glam::vec3(2.0, 0.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn client_reconnect_after_timeout() {
+        init();
+        let message = Message::from_bytes(&[11, 0, 0, 0]).expect("Failed to decode message");
+        assert!(matches!(message, Message::DisconnectByTimeout));
+
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = server.config();
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            server.socket.send_to(&[11, 0, 0, 0], data_client).unwrap();
+            server.accept();
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        match client.recv().expect("Failed to receive message") {
+            Message::DisconnectByTimeout => client.reconnect().expect("Failed to reconnect"),
+            val => panic!("Expected DisconnectByTimeout, got {:?}", val),
+        };
+        mock.join().unwrap();
+    }
+
+    #[test]
+    fn client_reconnect_multicast() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let group = Ipv4Addr::new(239, 255, 42, 99);
+        let data_port = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = NatNetConfig {
+            data_port,
+            transport: TransportMode::Multicast { group },
+            read_timeout: Some(Duration::from_secs(5)),
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            for _ in 0..2 {
+                let (_, client) = match server.socket.recv_from(&mut buf) {
+                    Ok(val) => val,
+                    Err(_) => return,
+                };
+                server
+                    .socket
+                    .send_to(&server_info_packet(server.natnet_version), client)
+                    .unwrap();
+            }
+        });
+        let mut client = match NatNetClient::connect(config) {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("Skipping, IPv4 multicast unsupported: {}", e);
+                return;
+            }
+        };
+        client.reconnect().expect("Failed to reconnect");
+        mock.join().unwrap();
+        assert_eq!(client.data_socket.local_addr().unwrap().port(), data_port);
+
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        sender.set_multicast_loop_v4(true).unwrap();
+        let frame = FrameData {
+            frame_number: 67,
+            ..Default::default()
+        };
+        if let Err(e) = sender.send_to(&frame.to_packet().unwrap(), (group, data_port)) {
+            log::warn!("Skipping, IPv4 multicast unsupported: {}", e);
+            return;
+        }
+        match client.recv().expect("Failed to receive frame") {
+            Message::FrameData(frame) => assert_eq!(frame.frame_number, 67),
+            val => panic!("Expected FrameData, got {:?}", val),
+        }
+    }
+
+    #[test]
+    fn client_refreshes_model_def() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = NatNetConfig {
+            auto_refresh_model_def: true,
+            ..server.config()
+        };
+        let mock = std::thread::spawn(move || {
+            let data_client = server.accept();
+            let frame = FrameData {
+                frame_number: 5,
+                frame_parameters: FrameParameters {
+                    param: 0x02,
+                    tracked_models_changed: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert!(frame.tracking_models_changed());
+            server
+                .socket
+                .send_to(&frame.to_packet().unwrap(), data_client)
+                .unwrap();
+            let mut buf = [0u8; 1024];
+            let (_, client) = server.socket.recv_from(&mut buf).unwrap();
+            assert_eq!(Message::peek_id(&buf), Some(MessageId::RequestModelDef));
+            let model = model_def(vec![ModelDefData::RigidBodyDesc {
+                size: 0,
+                data: Box::new(rigid_body_desc(5, "wand")),
+            }]);
+            server
+                .socket
+                .send_to(&model.to_packet().unwrap(), client)
+                .unwrap();
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        match client.recv().expect("Failed to receive frame") {
+            Message::FrameData(frame) => assert!(frame.tracking_models_changed()),
+            val => panic!("Expected FrameData, got {:?}", val),
+        };
+        match client.recv().expect("Failed to receive model def") {
+            Message::ModelDef(model) => assert!(model.rigid_body_desc(5).is_some()),
+            val => panic!("Expected ModelDef, got {:?}", val),
+        };
+        mock.join().unwrap();
+    }
+
+    #[test]
+    fn client_read_timeout() {
+        init();
+        let server = MockServer::new([4, 1, 0, 0]);
+        let config = server.config();
+        let mock = std::thread::spawn(move || {
+            server.accept();
+        });
+        let mut client = NatNetClient::connect(config).expect("Failed to connect");
+        mock.join().unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        let err = client.recv().expect_err("Expected a timeout");
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::Timeout)
+        );
+    }
+
+    #[test]
+    fn unrecognized_message_id() {
+        init();
+        let id = MessageId::from(42);
+        assert_eq!(id, MessageId::Unrecognized(42));
+        assert_eq!(u16::from(id), 42);
+        assert_eq!(u16::from(MessageId::from(7)), 7);
+
+        let err = Message::from_bytes(&[42, 0, 0, 0]).expect_err("Expected an error");
+        assert!(err.to_string().contains("42"), "{}", err);
+
+        let mut framer = MessageFramer::new();
+        framer.extend_from_slice(&[42, 0, 0, 0]);
+        let err = framer
+            .next_message()
+            .unwrap()
+            .expect_err("Expected an error");
+        assert_eq!(err.to_string(), "Unrecognized message ID: 42");
+    }
+
+    #[test]
+    fn marker_asset_roundtrip() {
+        let markers = MarkerAsset {
+            marker_count: 3,
+            marker_ids: vec![1, 2, 3],
+            marker_positions: vec![Vec3::X, Vec3::Y, Vec3::Z],
+            marker_sizes: vec![0.01, 0.012, 0.014],
+            marker_params: vec![0x02, 0x04, 0x01],
+            marker_residuals: vec![0.0001, 0.0002, 0.0],
+        };
+        let mut dst = BytesMut::new();
+        MarkerAssetCodec::default()
+            .encode(markers.clone(), &mut dst)
+            .unwrap();
+        assert_eq!(dst.len(), 4 + 3 * 26);
+        let decoded = MarkerAssetCodec::default().decode(&mut dst).unwrap();
+        assert_eq!(decoded, markers);
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn asset_decode_wire_layout() {
+        init();
+        // field by field as the NatNet SDK's UnpackAssetData reads them: asset id, rigid body
+        // count, then per rigid body id, position, orientation, mean error, and params, then the
+        // marker count and per marker (UnpackAssetMarkerData) id, position, size, params, and
+        // residual
+        let mut src = BytesMut::new();
+        src.put_u32_le(3);
+        src.put_u32_le(1);
+        src.put_u32_le(1);
+        for value in [1.0f32, 2.0, 3.0] {
+            src.put_f32_le(value);
+        }
+        for value in [0.0f32, 0.0, 0.0, 1.0] {
+            src.put_f32_le(value);
+        }
+        src.put_f32_le(0.001);
+        src.put_i16_le(1);
+        src.put_u32_le(2);
+        for (id, x, size, params, residual) in [
+            (11u32, 4.0f32, 0.014f32, 0x02i16, 0.0003f32),
+            (12, 5.0, 0.02, 0x04, 0.0),
+        ] {
+            src.put_u32_le(id);
+            for value in [x, 0.0, 0.0] {
+                src.put_f32_le(value);
+            }
+            src.put_f32_le(size);
+            src.put_i16_le(params);
+            src.put_f32_le(residual);
+        }
+        // the next asset's id, which a mis-sized marker would read into
+        src.put_u32_le(4);
+
+        let asset = AssetCodec::default().decode(&mut src).unwrap();
+        assert_eq!(src.len(), 4);
+        assert_eq!(asset.id, 3);
+        assert_eq!(asset.rigid_bodies.len(), 1);
+        assert_eq!(asset.rigid_bodies[0].pos, glam::vec3(1.0, 2.0, 3.0));
+        assert_eq!(asset.rigid_bodies[0].param, 1);
+        assert_eq!(asset.markers.marker_count, 2);
+        assert_eq!(asset.markers.marker_ids, vec![11, 12]);
+        assert_eq!(
+            asset.markers.marker_positions,
+            vec![glam::vec3(4.0, 0.0, 0.0), glam::vec3(5.0, 0.0, 0.0)]
+        );
+        assert_eq!(asset.markers.marker_sizes, vec![0.014, 0.02]);
+        assert_eq!(asset.markers.marker_params, vec![0x02, 0x04]);
+        assert_eq!(asset.markers.marker_residuals, vec![0.0003, 0.0]);
+    }
+
+    #[test]
+    fn asset_markers() {
+        init();
+        let markers = MarkerAsset {
+            marker_count: 2,
+            marker_ids: vec![11, 12],
+            marker_positions: vec![glam::vec3(1.0, 2.0, 3.0), glam::vec3(4.0, 5.0, 6.0)],
+            marker_sizes: vec![0.014, 0.02],
+            marker_params: vec![0x02, 0x04],
+            marker_residuals: vec![0.0003, 0.0],
+        };
+        let frame = FrameData {
+            asset_count: 1,
+            assets: vec![Asset {
+                id: 3,
+                rigid_body_count: 1,
+                rigid_bodies: vec![RigidBodyAsset {
+                    id: 1,
+                    pos: Vec3::ZERO,
+                    rot: Quat::IDENTITY,
+                    marker_error: 0.0,
+                    param: 0,
+                }],
+                markers: markers.clone(),
+            }],
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        let decoded = Message::from_bytes(&packet)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert_eq!(decoded.assets.len(), 1);
+        assert_eq!(decoded.assets[0].rigid_bodies.len(), 1);
+        assert_eq!(decoded.assets[0].markers, markers);
+    }
+
+    #[test]
+    fn id_only_header_layout() {
+        init();
+        let frame = FrameData {
+            frame_number: 7,
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        let mut id_only = packet[..2].to_vec();
+        id_only.extend_from_slice(&packet[4..]);
+        let message = Message::from_bytes_with_layout(&id_only, HeaderLayout::IdOnly)
+            .expect("Failed to decode id-only message");
+        assert_eq!(message.as_frame_data().map(|f| f.frame_number), Some(7));
+
+        let message = Message::from_bytes_with_layout(&packet, HeaderLayout::IdAndSize).unwrap();
+        assert_eq!(message.as_frame_data().map(|f| f.frame_number), Some(7));
+    }
+
+    #[test]
+    fn message_accessors() {
+        init();
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA).unwrap();
+        assert_eq!(
+            frame.as_frame_data().map(|f| f.frame_number),
+            Some(169383987)
+        );
+        assert!(frame.as_model_def().is_none());
+        assert!(frame.into_frame_data().is_some());
+
+        let ping_response = Message::from_bytes(&server_info_packet([4, 1, 0, 0])).unwrap();
+        assert!(ping_response.as_frame_data().is_none());
+        assert!(ping_response.into_model_def().is_none());
+    }
+
+    #[test]
+    fn ping_exchange() {
+        init();
+        let ping = Message::ping().to_bytes().unwrap();
+        assert_eq!(Message::peek_id(&ping), Some(MessageId::Ping));
+        match Message::from_bytes(&ping).unwrap() {
+            Message::Ping(sender) => {
+                assert_eq!(sender.app_name.trim_end_matches('\0'), "Ping");
+                assert_eq!(sender.natnet_version, VERSION);
+            }
+            val => panic!("Expected Ping, got {:?}", val),
+        }
+
+        let server_info = match Message::from_bytes(&server_info_packet([4, 1, 0, 0])).unwrap() {
+            Message::PingResponse(server_info) => *server_info,
+            val => panic!("Expected PingResponse, got {:?}", val),
+        };
+        let response = Message::ping_response(server_info.clone())
+            .to_bytes()
+            .unwrap();
+        assert_eq!(Message::peek_id(&response), Some(MessageId::PingResponse));
+        assert_eq!(&response[..], &server_info_packet([4, 1, 0, 0])[..]);
+        match Message::from_bytes(&response).unwrap() {
+            Message::PingResponse(decoded) => assert_eq!(*decoded, server_info),
+            val => panic!("Expected PingResponse, got {:?}", val),
+        }
+    }
+
+    #[test]
+    fn encode_unsupported_message() {
+        let err = Message::Unknown
+            .to_bytes()
+            .expect_err("Encoded an unknown message");
+        assert_eq!(
+            err.downcast_ref::<EncodeError>(),
+            Some(&EncodeError::Unsupported("Unknown"))
+        );
+        let err = Message::Response(Box::new(Response::Int(1)))
+            .to_bytes()
+            .expect_err("Encoded a Response");
+        assert_eq!(
+            err.downcast_ref::<EncodeError>(),
+            Some(&EncodeError::Unsupported("Response"))
+        );
+    }
+
+    #[test]
+    fn camera_desc_extra_fields() {
+        init();
+        let mut camera = BytesMut::new();
+        camera.extend_from_slice(b"Prime 41\0");
+        for v in [1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 1.0] {
+            camera.put_f32_le(v);
+        }
+        camera.put_i32_le(12);
+        camera.put_i32_le(2048);
+        camera.put_i32_le(2048);
+        let mut src = BytesMut::new();
+        src.put_u16_le(0);
+        src.put_u32_le(1);
+        src.put_u32_le(5);
+        src.put_u32_le(camera.len() as u32);
+        src.extend_from_slice(&camera);
+        let model = ModelDefCodec::default()
+            .decode(&mut src)
+            .expect("Failed to decode ModelDef");
+        match &model.dataset[0] {
+            ModelDefData::CameraDesc { data, .. } => {
+                assert_eq!(data.name, "Prime 41");
+                assert_eq!(data.id, Some(12));
+                assert_eq!(data.resolution, Some([2048, 2048]));
+                assert_eq!(data.pos, glam::vec3(1.0, 2.0, 3.0));
+                assert_eq!(data.status, None);
+            }
+            val => panic!("Expected CameraDesc, got {:?}", val),
+        };
+
+        // the status flags trail the resolution on newer servers
+        camera.put_u32_le(0x03);
+        let mut src = BytesMut::new();
+        src.put_u16_le(0);
+        src.put_u32_le(1);
+        src.put_u32_le(5);
+        src.put_u32_le(camera.len() as u32);
+        src.extend_from_slice(&camera);
+        let packet = src.clone();
+        let mut codec = ModelDefCodec::default();
+        let model = codec.decode(&mut src).expect("Failed to decode ModelDef");
+        match &model.dataset[0] {
+            ModelDefData::CameraDesc { data, .. } => {
+                let status = data.status.expect("Missing CameraStatus");
+                assert!(status.enabled());
+                assert!(status.is_reference());
+                assert_eq!(status, CameraStatus::from_bits(0x03));
+            }
+            val => panic!("Expected CameraDesc, got {:?}", val),
+        };
+        let mut encoded = BytesMut::new();
+        codec.encode(model, &mut encoded).unwrap();
+        assert_eq!(encoded, packet);
+
+        let packet = sample_model_def_packet();
+        let model = Message::from_bytes(&packet)
+            .unwrap()
+            .into_model_def()
+            .unwrap();
+        assert!(model.dataset.iter().all(|data| match data {
+            ModelDefData::CameraDesc { data, .. } => data.id.is_none(),
+            _ => true,
+        }));
+    }
+
+    #[test]
+    fn all_labeled_and_unlabeled() {
+        let frame = FrameData {
+            unlabeled_marker_count: 1,
+            unlabeled_marker_positions: vec![glam::vec3(1.0, 2.0, 3.0)],
+            labeled_marker_positions: vec![LabeledMarker {
+                pos: glam::vec3(4.0, 5.0, 6.0),
+                ..labeled_marker(7, 0.01, LabeledMarkerStatus::PointCloudSolved)
+            }],
+            ..Default::default()
+        };
+        let markers: Vec<(Option<u32>, Vec3)> = frame.all_labeled_and_unlabeled().collect();
+        assert_eq!(
+            markers,
+            vec![
+                (Some(7), glam::vec3(4.0, 5.0, 6.0)),
+                (None, glam::vec3(1.0, 2.0, 3.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_section_pass_through() {
+        init();
+        let mut codec = FrameDataCodec {
+            keep_raw_sections: true,
+            ..Default::default()
+        };
+        let mut src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        let frame = codec.decode(&mut src).expect("Failed to decode frame");
+        assert!(frame.raw_sections.rigid_bodies.is_some());
+        let mut dst = BytesMut::new();
+        codec.encode(frame.clone(), &mut dst).unwrap();
+        assert_eq!(&dst[..], &SAMPLE_FRAME_DATA[2..]);
+
+        let mut modified = frame.clone();
+        modified.rigid_bodies[0].pos = glam::vec3(1.0, 2.0, 3.0);
+        modified.raw_sections.rigid_bodies = None;
+        let mut dst = BytesMut::new();
+        codec.encode(modified, &mut dst).unwrap();
+        let decoded = codec.decode(&mut dst).unwrap();
+        assert_eq!(decoded.rigid_bodies[0].pos, glam::vec3(1.0, 2.0, 3.0));
+        assert_eq!(decoded.markersets, frame.markersets);
+    }
+
+    #[test]
+    fn drop_frame_timecode() {
+        // 00:10:00;00 is exactly 17982 frames into drop-frame timecode
+        let tc = SmpteTimecode::from_raw(0x000a_0000 | DROP_FRAME_FLAG, 0);
+        assert!(tc.drop_frame);
+        assert_eq!(tc.minutes, 10);
+        assert_eq!(tc.frames, 0);
+        assert!((tc.timecode_seconds(30) - 17982.0 * 1001.0 / 30000.0).abs() < 1e-9);
+
+        // frames 00 and 01 don't exist at minute 1, so 00:01:00;02 is frame 1800
+        let tc = SmpteTimecode::from_raw(0x0001_0002 | DROP_FRAME_FLAG, 0);
+        assert_eq!(tc.frames, 2);
+        assert!((tc.timecode_seconds(30) - 60.06).abs() < 1e-9);
+        assert_eq!(tc.to_raw(), (0x0001_0002 | DROP_FRAME_FLAG, 0));
+
+        let tc = SmpteTimecode::from_raw(0x0001_0002, 0);
+        assert!(!tc.drop_frame);
+        assert!((tc.timecode_seconds(30) - (60.0 + 2.0 / 30.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn valid_timecode() {
+        assert!(!FrameData::default().has_valid_timecode());
+        let (timecode, timecode_sub) = SmpteTimecode {
+            hours: 1,
+            frames: 12,
+            ..Default::default()
+        }
+        .to_raw();
+        let frame = FrameData {
+            timecode,
+            timecode_sub,
+            ..Default::default()
+        };
+        assert!(frame.has_valid_timecode());
+        let frame = FrameData {
+            timecode_sub: 3,
+            ..Default::default()
+        };
+        assert!(frame.has_valid_timecode());
+    }
+
+    #[test]
+    fn rigid_body_relative_pose() {
+        let base = rigid_body(
+            1,
+            glam::vec3(1.0, 0.0, 0.0),
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        );
+        let tool = rigid_body(
+            2,
+            glam::vec3(1.0, 2.0, 0.0),
+            Quat::from_rotation_z(std::f32::consts::PI),
+        );
+        let (pos, rot) = tool.relative_to(&base);
+        assert!(pos.abs_diff_eq(glam::vec3(2.0, 0.0, 0.0), 1e-6));
+        assert!(rot.abs_diff_eq(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2), 1e-6));
+
+        let (pos, rot) = base.relative_to(&base);
+        assert!(pos.abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!(rot.abs_diff_eq(Quat::IDENTITY, 1e-6));
+    }
+
+    #[test]
+    fn markerset_labeled_positions() {
+        let mut markerset = MarkerSet::new("wand", 3);
+        markerset.positions = vec![Vec3::X, Vec3::Y, Vec3::Z];
+        let mut desc = MarkerSetDesc::new("wand", 3);
+        desc.marker_names = vec!["tip".into(), "mid".into(), "base".into()];
+        let labeled: Vec<_> = markerset.labeled_positions(&desc).collect();
+        assert_eq!(
+            labeled,
+            vec![
+                (Cow::from("tip"), Vec3::X),
+                (Cow::from("mid"), Vec3::Y),
+                (Cow::from("base"), Vec3::Z),
+            ]
+        );
+
+        desc.marker_names.pop();
+        let names: Vec<_> = markerset.labeled_positions(&desc).map(|(n, _)| n).collect();
+        assert_eq!(names, ["tip", "mid", "2"]);
+    }
+
+    #[test]
+    fn detect_frame_layout_version() {
+        let src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        let version = FrameDataCodec::detect_version(&src).expect("Failed to detect version");
+        assert!(version >= NatNetVersion::new(3, 0, 0, 0));
+        assert_eq!(version, NatNetVersion::new(4, 1, 0, 0));
+
+        // packet size, frame number, no markersets or unlabeled markers, and one rigid body
+        let mut src = BytesMut::new();
+        src.put_u16_le(0);
+        src.put_u32_le(1);
+        src.put_u32_le(0);
+        src.put_u32_le(0);
+        src.put_u32_le(1);
+        src.put_u32_le(5);
+        for v in [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0] {
+            src.put_f32_le(v);
+        }
+        let mut pre_3_0 = src.clone();
+        src.put_f32_le(0.0005);
+        src.put_i16_le(1);
+        assert_eq!(
+            FrameDataCodec::detect_version(&src),
+            Some(NatNetVersion::new(3, 0, 0, 0))
+        );
+        pre_3_0.put_u32_le(3);
+        pre_3_0.extend_from_slice(&[0; 3 * 20 + 6]);
+        assert_eq!(
+            FrameDataCodec::detect_version(&pre_3_0),
+            Some(NatNetVersion::new(2, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DecodeError>();
+        assert_send_sync::<EncodeError>();
+        assert_send_sync::<Message>();
+        assert_send_sync::<MessageFramer>();
+        assert_send_sync::<MessageReader<std::fs::File>>();
+        assert_send_sync::<CaptureIndex<'static>>();
+        assert_send_sync::<MessageId>();
+        assert_send_sync::<HeaderLayout>();
+        assert_send_sync::<PingResponseCodec>();
+        assert_send_sync::<PingResponse>();
+        assert_send_sync::<ResponseCodec>();
+        assert_send_sync::<Response>();
+        assert_send_sync::<NatNetVersion>();
+        assert_send_sync::<FrameDataCodec>();
+        assert_send_sync::<FrameData>();
+        assert_send_sync::<RawSections>();
+        assert_send_sync::<FrameSummary>();
+        assert_send_sync::<FrameDedup>();
+        assert_send_sync::<ValidationWarning>();
+        assert_send_sync::<FrameRing>();
+        assert_send_sync::<LazyFrame>();
+        assert_send_sync::<StreamingId>();
+        assert_send_sync::<ModelDefBuilder>();
+        assert_send_sync::<StringInterner>();
+        assert_send_sync::<DecodeDiagnostics>();
+        assert_send_sync::<MarkerSwapDetector>();
+        assert_send_sync::<ReorderBuffer>();
+        assert_send_sync::<AssetFlags>();
+        #[cfg(feature = "ros")]
+        assert_send_sync::<RosPose>();
+        #[cfg(feature = "c3d")]
+        assert_send_sync::<C3dWriter>();
+        assert_send_sync::<ModelDefCodec>();
+        assert_send_sync::<ModelDef>();
+        assert_send_sync::<ModelDefData>();
+        assert_send_sync::<Vec3Codec>();
+        assert_send_sync::<QuatCodec>();
+        assert_send_sync::<MarkerAssetCodec>();
+        assert_send_sync::<MarkerAsset>();
+        assert_send_sync::<MarkerSetCodec>();
+        assert_send_sync::<MarkerSet>();
+        assert_send_sync::<RigidBodyCodec>();
+        assert_send_sync::<RigidBody>();
+        assert_send_sync::<RigidBodyAssetCodec>();
+        assert_send_sync::<RigidBodyAsset>();
+        assert_send_sync::<SkeletonCodec>();
+        assert_send_sync::<Skeleton>();
+        assert_send_sync::<AssetCodec>();
+        assert_send_sync::<Asset>();
+        assert_send_sync::<LabeledMarkerCodec>();
+        assert_send_sync::<LabeledMarker>();
+        assert_send_sync::<LabeledMarkerStatus>();
+        assert_send_sync::<ForcePlateCodec>();
+        assert_send_sync::<ForcePlate>();
+        assert_send_sync::<ForcePlateChannelCodec>();
+        assert_send_sync::<ForcePlateChannel>();
+        assert_send_sync::<DeviceCodec>();
+        assert_send_sync::<Device>();
+        assert_send_sync::<DeviceChannelCodec>();
+        assert_send_sync::<DeviceChannel>();
+        assert_send_sync::<StampsCodec>();
+        assert_send_sync::<Stamps>();
+        assert_send_sync::<FrameParametersCodec>();
+        assert_send_sync::<FrameParameters>();
+        assert_send_sync::<SmpteTimecode>();
+        assert_send_sync::<MarkerSetDescCodec>();
+        assert_send_sync::<MarkerSetDesc>();
+        assert_send_sync::<RigidBodyDescCodec>();
+        assert_send_sync::<RigidBodyDesc>();
+        assert_send_sync::<CameraDescCodec>();
+        assert_send_sync::<CameraDesc>();
+        assert_send_sync::<CameraStatus>();
+        assert_send_sync::<AssetDescCodec>();
+        assert_send_sync::<AssetDesc>();
+        assert_send_sync::<TransportMode>();
+        assert_send_sync::<NatNetConfig>();
+        assert_send_sync::<NatNetClient>();
+        #[cfg(feature = "tokio")]
+        assert_send_sync::<NatNetFrame>();
+    }
+
+    #[test]
+    fn frame_parameters_bump_detected() {
+        let mut src = BytesMut::from(&0x11i16.to_le_bytes()[..]);
+        let params = FrameParametersCodec::default().decode(&mut src).unwrap();
+        assert!(params.is_recording);
+        assert!(!params.tracked_models_changed);
+        assert!(params.bump_detected);
+
+        let mut src = BytesMut::from(&0x03i16.to_le_bytes()[..]);
+        let params = FrameParametersCodec::default().decode(&mut src).unwrap();
+        assert!(!params.bump_detected);
+    }
+
+    #[test]
+    fn framer_visits_rigid_bodies() {
+        init();
+        let frame = |frame_number, ids: &[u32]| FrameData {
+            frame_number,
+            rigid_body_count: ids.len() as u32,
+            rigid_bodies: ids
+                .iter()
+                .map(|&id| rigid_body(id, Vec3::ZERO, Quat::IDENTITY))
+                .collect(),
+            ..Default::default()
+        };
+        let mut stream = frame(1, &[1, 2, 3]).to_packet().unwrap();
+        stream.extend_from_slice(&frame(2, &[10, 20]).to_packet().unwrap());
+
+        let mut framer = MessageFramer::new();
+        let mut sum = 0;
+        // the second frame is split across calls and completed from the persistent buffer
+        let split = stream.len() - 10;
+        let frames = framer
+            .for_each_rigid_body(&stream[..split], |rb| sum += rb.id)
+            .unwrap();
+        assert_eq!((frames, sum), (1, 6));
+        let frames = framer
+            .for_each_rigid_body(&stream[split..], |rb| sum += rb.id)
+            .unwrap();
+        assert_eq!((frames, sum), (1, 36));
+        assert_eq!(framer.buffered(), 0);
+
+        let mut sum = 0;
+        let (frame_number, _) = FrameDataCodec::default()
+            .for_each_rigid_body(&SAMPLE_FRAME_DATA[2..], |rb| sum += rb.id)
+            .unwrap();
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert_eq!(frame_number, frame.frame_number);
+        assert_eq!(sum, frame.rigid_bodies.iter().map(|rb| rb.id).sum::<u32>());
+    }
+
+    #[test]
+    fn unlabeled_marker_ids() {
+        init();
+        let frame = FrameData {
+            frame_number: 3,
+            unlabeled_marker_count: 2,
+            unlabeled_marker_positions: vec![Vec3::X, Vec3::Y],
+            unlabeled_marker_ids: vec![7, 9],
+            rigid_body_count: 1,
+            rigid_bodies: vec![rigid_body(5, Vec3::ONE, Quat::IDENTITY)],
+            ..Default::default()
+        };
+        let packet = frame.to_packet().unwrap();
+        let mut src = BytesMut::from(&packet[2..]);
+        assert_eq!(
+            FrameDataCodec::detect_version(&src),
+            Some(NatNetVersion::new(4, 1, 0, 0))
+        );
+        let decoded = FrameDataCodec::default().decode(&mut src).unwrap();
+        assert_eq!(decoded.unlabeled_marker_bytes, 32);
+        assert_eq!(decoded.unlabeled_marker_ids, vec![7, 9]);
+        assert_eq!(
+            decoded.unlabeled_marker_positions,
+            frame.unlabeled_marker_positions
+        );
+        assert_eq!(decoded.rigid_bodies, frame.rigid_bodies);
+
+        let sample = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert!(sample.unlabeled_marker_ids.is_empty());
+    }
+
+    #[test]
+    fn frame_without_asset_section() {
+        init();
+        let body = rigid_body(5, Vec3::ONE, Quat::IDENTITY);
+        let marker = labeled_marker(5 << 16 | 1, 0.01, LabeledMarkerStatus::ModelSolved);
+        // 3.x layout: no section byte counts and labeled markers directly after skeletons
+        let mut src = BytesMut::new();
+        src.put_u16_le(0);
+        src.put_u32_le(12);
+        src.put_u32_le(1);
+        src.extend_from_slice(b"wand\0");
+        src.put_u32_le(1);
+        src.extend_from_slice(&bincode::serialize(&Vec3::X).unwrap());
+        src.put_u32_le(0);
+        src.put_u32_le(1);
+        RigidBodyCodec::default()
+            .encode(body.clone(), &mut src)
+            .unwrap();
+        src.put_u32_le(0);
+        src.put_u32_le(1);
+        LabeledMarkerCodec::default()
+            .encode(marker.clone(), &mut src)
+            .unwrap();
+        src.put_u32_le(0);
+        src.put_u32_le(0);
+        src.put_u32_le(0x1234);
+        src.put_u32_le(0);
+
+        let mut codec = FrameDataCodec::new(NatNetVersion::new(3, 1, 0, 0));
+        let frame = codec.decode(&mut src.clone()).unwrap();
+        assert_eq!(frame.frame_number, 12);
+        assert_eq!(frame.markersets[0].positions, vec![Vec3::X]);
+        assert_eq!(frame.rigid_bodies, vec![body]);
+        assert_eq!(frame.asset_count, 0);
+        assert!(frame.assets.is_empty());
+        assert_eq!(frame.labeled_marker_positions, vec![marker]);
+        assert_eq!(frame.timecode, 0x1234);
+
+        let mut encoded = BytesMut::new();
+        codec.encode(frame, &mut encoded).unwrap();
+        // the decoder defaulted the missing stamps and suffix, which are re-encoded
+        assert_eq!(encoded[..src.len()], src[..]);
+    }
+
+    #[test]
+    fn precision_transmit_timestamp() {
+        init();
+        let frame = FrameData {
+            frame_number: 7,
+            precision_transmit_timestamp: Some(0x0123_4567_89ab_cdef),
+            ..Default::default()
+        };
+        // a pre 4.1 layout, without section byte counts or assets
+        let mut packet = BytesMut::new();
+        FrameDataCodec::new(NatNetVersion::new(3, 0, 0, 0))
+            .encode(frame, &mut packet)
+            .unwrap();
+        let decoded = FrameDataCodec::new(NatNetVersion::new(3, 0, 0, 0))
+            .decode(&mut packet.clone())
+            .unwrap();
+        assert_eq!(
+            decoded.precision_transmit_timestamp,
+            Some(0x0123_4567_89ab_cdef)
+        );
+
+        // older servers don't send it, so the trailing bytes are left alone
+        let mut src = packet.clone();
+        let decoded = FrameDataCodec::new(NatNetVersion::new(2, 9, 0, 0))
+            .decode(&mut src)
+            .unwrap();
+        assert_eq!(decoded.precision_transmit_timestamp, None);
+        assert_eq!(src.len(), 8);
+
+        let sample = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert_eq!(sample.precision_transmit_timestamp, None);
+    }
+
+    #[test]
+    fn precision_transmit_timestamp_stream() {
+        init();
+        // frames without a timestamp back to back, so 8 bytes always follow the first frame
+        let mut src = BytesMut::new();
+        for frame_number in [1, 2] {
+            let frame = FrameData {
+                frame_number,
+                ..Default::default()
+            };
+            src.extend_from_slice(&frame.to_packet().unwrap());
+        }
+        for frame_number in [1, 2] {
+            let frame = Message::decode(&mut src)
+                .unwrap()
+                .into_frame_data()
+                .expect("Expected FrameData");
+            assert_eq!(frame.frame_number, frame_number);
+            assert_eq!(frame.precision_transmit_timestamp, None);
+        }
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn device_channel_sample_rate() {
+        let channel = |value_count| DeviceChannel {
+            value_count,
+            values: vec![0; value_count as usize],
+        };
+        let device = Device {
+            id: 1,
+            device_type: None,
+            channel_count: 2,
+            channels: vec![channel(10), channel(1)],
+        };
+        let rates: Vec<f32> = device
+            .channels
+            .iter()
+            .map(|ch| ch.sample_rate(120.0))
+            .collect();
+        assert_eq!(rates, vec![1200.0, 120.0]);
+    }
+
+    #[test]
+    fn device_type() {
+        init();
+        let device = |device_type| Device {
+            id: 3,
+            device_type,
+            channel_count: 2,
+            channels: vec![
+                DeviceChannel {
+                    value_count: 1,
+                    values: vec![10],
+                },
+                DeviceChannel {
+                    value_count: 2,
+                    values: vec![20, 21],
+                },
+            ],
+        };
+        for device_type in [Some(9), None] {
+            let frame = FrameData {
+                frame_number: 11,
+                device_count: 1,
+                devices: vec![device(device_type)],
+                timecode: 42,
+                ..Default::default()
+            };
+            let packet = frame.to_packet().unwrap();
+            let decoded = FrameDataCodec::default()
+                .decode(&mut BytesMut::from(&packet[2..]))
+                .unwrap();
+            assert_eq!(decoded.devices, vec![device(device_type)]);
+            // the rest of the frame stays aligned
+            assert_eq!(decoded.timecode, 42);
+        }
+    }
+
+    #[test]
+    fn lazy_frame_sections() {
+        init();
+        let lazy = LazyFrame::new(BytesMut::from(&SAMPLE_FRAME_DATA[2..])).unwrap();
+        assert_eq!(lazy.frame_number(), 169383987);
+        assert_eq!(lazy.rigid_body_count(), 5);
+        assert!(lazy.rigid_bodies.get().is_none());
+
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        let rigid_bodies = lazy.rigid_bodies().unwrap();
+        assert_eq!(rigid_bodies, &frame.rigid_bodies[..]);
+        // later calls return the cached section
+        assert!(std::ptr::eq(rigid_bodies, lazy.rigid_bodies().unwrap()));
+        assert!(lazy.markersets.get().is_none());
+        assert_eq!(lazy.markersets().unwrap(), &frame.markersets[..]);
+
+        let err = LazyFrame::new(BytesMut::from(&SAMPLE_FRAME_DATA[2..100])).unwrap_err();
+        assert!(matches!(err, DecodeError::NotEnoughBytes { .. }));
+    }
+
+    #[test]
+    fn decode_rigid_bodies_only() {
+        init();
+        let mut src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        let (frame_number, rigid_bodies) = FrameDataCodec::default()
+            .decode_rigid_bodies_only(&mut src)
+            .unwrap();
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert_eq!(frame_number, 169383987);
+        assert_eq!(rigid_bodies.len(), 5);
+        assert_eq!(rigid_bodies, frame.rigid_bodies);
+    }
+
+    #[test]
+    fn precheck_truncated_frame() {
+        let src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        assert_eq!(FrameDataCodec::precheck(&src), Ok(()));
+
+        let truncated = BytesMut::from(&SAMPLE_FRAME_DATA[2..200]);
+        assert!(matches!(
+            FrameDataCodec::precheck(&truncated),
+            Err(DecodeError::NotEnoughBytes { .. })
+        ));
+    }
+
+    #[test]
+    fn frame_summary() {
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        let summary = frame.summary();
+        assert_eq!(summary.frame_number, 169383987);
+        assert_eq!(summary.markersets, 6);
+        assert_eq!(summary.markerset_bytes, 1678);
+        assert_eq!(summary.rigid_bodies, 5);
+        assert_eq!(summary.rigid_body_bytes, 5 * 38);
+        assert_eq!(summary.labeled_markers, 0);
+        assert_eq!(summary.total_markers, 0);
+        assert_eq!(summary.skeletons, 0);
+        assert_eq!(summary.assets, 0);
+    }
+
+    #[test]
+    fn rigid_body_to_matrix4() {
+        let body = rigid_body(
+            1,
+            glam::vec3(1.0, 2.0, 3.0),
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        );
+        let m = body.to_matrix4();
+        assert_eq!(m[3], [1.0, 2.0, 3.0, 1.0]);
+        // x maps to y and y maps to -x
+        let expected = [
+            [0.0, 1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ];
+        for (col, expected) in m[..3].iter().zip(expected.iter()) {
+            for (v, e) in col.iter().zip(expected.iter()) {
+                assert!((v - e).abs() < 1e-6, "{:?}", m);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_frame() {
+        init();
+        // frame number, then a zero count and byte count for each of the 8 sections and timecodes
+        let mut packet = BytesMut::new();
+        packet.put_u16_le(MessageId::FrameData.into());
+        packet.put_u16_le(4 + 8 * 8 + 8);
+        packet.put_u32_le(42);
+        packet.extend_from_slice(&[0; 8 * 8 + 8]);
+        let frame = Message::from_bytes(&packet)
+            .expect("Failed to decode empty frame")
+            .into_frame_data()
+            .expect("Expected FrameData");
+        assert_eq!(frame.frame_number, 42);
+        assert_eq!(
+            *frame,
+            FrameData {
+                packet_size: 4 + 8 * 8 + 8,
+                frame_number: 42,
+                ..Default::default()
+            }
+        );
+
+        // elements with nothing in them are only their counts
+        let mut src = BytesMut::from(&b"empty\0\0\0\0\0"[..]);
+        let markerset = MarkerSetCodec::default().decode(&mut src).unwrap();
+        assert_eq!(markerset, MarkerSet::new("empty", 0));
+        let mut src = BytesMut::from(&[0u8; 4][..]);
+        let channel = DeviceChannelCodec::default().decode(&mut src).unwrap();
+        assert!(channel.values.is_empty());
+    }
+
+    #[test]
+    fn decode_into_reuses_allocations() {
+        init();
+        let mut codec = FrameDataCodec::default();
+        let mut frame = FrameData::default();
+        let mut src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        codec.decode_into(&mut src, &mut frame).unwrap();
+        let expected = frame.clone();
+        let markersets_ptr = frame.markersets.as_ptr();
+        let rigid_bodies_ptr = frame.rigid_bodies.as_ptr();
+        let names_ptr: Vec<_> = frame.markersets.iter().map(|m| m.name.as_ptr()).collect();
+
+        let mut src = BytesMut::from(&SAMPLE_FRAME_DATA[2..]);
+        codec.decode_into(&mut src, &mut frame).unwrap();
+        assert_eq!(frame, expected);
+        assert_eq!(frame.markersets.as_ptr(), markersets_ptr);
+        assert_eq!(frame.rigid_bodies.as_ptr(), rigid_bodies_ptr);
+        let reused: Vec<_> = frame.markersets.iter().map(|m| m.name.as_ptr()).collect();
+        assert_eq!(reused, names_ptr);
+    }
+
+    #[test]
+    fn decode_into_keeps_peak_capacity() {
+        init();
+        let packet = |count: usize| {
+            let frame = FrameData {
+                unlabeled_marker_count: count as u32,
+                unlabeled_marker_positions: vec![Vec3::ONE; count],
+                labeled_marker_count: count as u32,
+                labeled_marker_positions: (0..count as u32)
+                    .map(|id| labeled_marker(id, 0.01, LabeledMarkerStatus::PointCloudSolved))
+                    .collect(),
+                ..Default::default()
+            };
+            frame.to_packet().unwrap()
+        };
+        let mut codec = FrameDataCodec::default();
+        let mut frame = FrameData::default();
+        codec
+            .decode_into(&mut BytesMut::from(&packet(10)[2..]), &mut frame)
+            .unwrap();
+        let unlabeled = (
+            frame.unlabeled_marker_positions.as_ptr(),
+            frame.unlabeled_marker_positions.capacity(),
+        );
+        let labeled = (
+            frame.labeled_marker_positions.as_ptr(),
+            frame.labeled_marker_positions.capacity(),
+        );
+        for count in [5, 10] {
+            codec
+                .decode_into(&mut BytesMut::from(&packet(count)[2..]), &mut frame)
+                .unwrap();
+            assert_eq!(frame.unlabeled_marker_positions.len(), count);
+            assert_eq!(frame.labeled_marker_positions.len(), count);
+            let reused = (
+                frame.unlabeled_marker_positions.as_ptr(),
+                frame.unlabeled_marker_positions.capacity(),
+            );
+            assert_eq!(reused, unlabeled);
+            let reused = (
+                frame.labeled_marker_positions.as_ptr(),
+                frame.labeled_marker_positions.capacity(),
+            );
+            assert_eq!(reused, labeled);
+        }
+
+        codec
+            .decode_into(&mut BytesMut::from(&packet(5)[2..]), &mut frame)
+            .unwrap();
+        frame.shrink_to_fit();
+        assert_eq!(frame.unlabeled_marker_positions.capacity(), 5);
+        assert_eq!(frame.labeled_marker_positions.capacity(), 5);
+    }
+
+    // src/ModelDef.bin was captured 4 bytes short, cutting off the end of its last CameraDesc.
+    // This is the capture without that element, with its dataset count and packet size updated.
+    fn sample_model_def_packet() -> Vec<u8> {
+        let capture = std::fs::read("src/ModelDef.bin").unwrap();
+        let dataset_count = u32::from_le_bytes(capture[4..8].try_into().unwrap());
+        let mut end = 8;
+        for _ in 0..dataset_count - 1 {
+            let size = u32::from_le_bytes(capture[end + 4..end + 8].try_into().unwrap());
+            end += 8 + size as usize;
+        }
+        let mut packet = capture[..end].to_vec();
+        packet[2..4].copy_from_slice(&((end - 4) as u16).to_le_bytes());
+        packet[4..8].copy_from_slice(&(dataset_count - 1).to_le_bytes());
+        packet
+    }
+
+    #[test]
+    fn parse_modeldef() {
+        init();
+        let path = std::path::PathBuf::from("src/ModelDef.bin");
+        let packet = std::fs::read(path).unwrap();
+        // the truncated capture is an error rather than a panic
+        let err = Message::from_bytes(&packet).expect_err("Expected truncated CameraDesc");
+        assert!(matches!(
+            err.downcast_ref::<DecodeError>(),
+            Some(DecodeError::NotEnoughBytes {
+                item: "CameraDesc",
+                ..
+            })
+        ));
+        let message = Message::from_bytes(&sample_model_def_packet());
         assert!(message.is_ok());
     }
+
+    #[test]
+    fn modeldef_round_trip() {
+        init();
+        let packet = sample_model_def_packet();
+        let message = Message::from_bytes(&packet).expect("Failed to decode message");
+        let encoded = message.to_bytes().expect("Failed to encode message");
+        assert_eq!(Message::peek_id(&encoded), Some(MessageId::ModelDef));
+        let decoded = Message::from_bytes(&encoded).expect("Failed to decode encoded message");
+        assert_eq!(decoded.as_model_def(), message.as_model_def());
+        assert_eq!(&encoded[..], &packet[..]);
+    }
+
+    #[test]
+    fn modeldef_builder() {
+        init();
+        let model = ModelDefBuilder::new()
+            .add_markerset_desc(MarkerSetDesc::new("wand", 0))
+            .add_rigid_body_desc(rigid_body_desc(5, "wand"))
+            .build();
+        assert_eq!(model.dataset_count, 2);
+        assert_eq!(model.dataset.len(), 2);
+
+        let packet = model.to_packet().unwrap();
+        let decoded = match Message::from_bytes(&packet).expect("Failed to decode message") {
+            Message::ModelDef(model) => model,
+            val => panic!("Expected ModelDef, got {:?}", val),
+        };
+        assert_eq!(decoded.dataset_count, 2);
+        assert!(model.diff(&decoded).is_empty());
+        assert_eq!(decoded.rigid_body_desc(5), model.rigid_body_desc(5));
+    }
+
+    #[test]
+    fn modeldef_without_element_sizes() {
+        init();
+        let mut markerset = MarkerSetDesc::new("wand", 2);
+        markerset.marker_names = vec!["tip".into(), "base".into()];
+        let rigid_body = rigid_body_desc(5, "wand");
+        let camera = CameraDesc {
+            name: "cam".into(),
+            pos: Vec3::ONE,
+            rot: Quat::IDENTITY,
+            id: None,
+            resolution: None,
+            status: None,
+        };
+        // pre 4.1 layout: each element is its type followed directly by its description
+        let mut elements = BytesMut::new();
+        elements.put_u32_le(0);
+        MarkerSetDescCodec
+            .encode(markerset.clone(), &mut elements)
+            .unwrap();
+        let markerset_size = elements.len() as u32 - 4;
+        elements.put_u32_le(1);
+        RigidBodyDescCodec::default()
+            .encode(rigid_body.clone(), &mut elements)
+            .unwrap();
+        let rigid_body_size = elements.len() as u32 - 8 - markerset_size;
+        elements.put_u32_le(5);
+        CameraDescCodec
+            .encode(camera.clone(), &mut elements)
+            .unwrap();
+        let mut src = BytesMut::new();
+        src.put_u16_le(elements.len() as u16 + 4);
+        src.put_u32_le(3);
+        src.extend_from_slice(&elements);
+        let packet = src.clone();
+
+        let mut codec = ModelDefCodec::new(NatNetVersion::new(3, 0, 0, 0));
+        let model = codec.decode(&mut src).expect("Failed to decode ModelDef");
+        assert!(src.is_empty());
+        assert_eq!(
+            model.dataset,
+            vec![
+                ModelDefData::MarkerSetDesc {
+                    size: markerset_size,
+                    data: Box::new(markerset),
+                },
+                ModelDefData::RigidBodyDesc {
+                    size: rigid_body_size,
+                    data: Box::new(rigid_body),
+                },
+                ModelDefData::CameraDesc {
+                    size: 4 + 28,
+                    data: Box::new(camera),
+                },
+            ]
+        );
+
+        let mut encoded = BytesMut::new();
+        codec.encode(model, &mut encoded).unwrap();
+        assert_eq!(encoded, packet);
+    }
+
+    #[test]
+    fn rigid_body_desc_empty_name() {
+        init();
+        let mut src = BytesMut::new();
+        src.put_u8(0);
+        src.put_i32_le(4);
+        src.put_i32_le(-1);
+        src.extend_from_slice(&[0; 12]);
+        src.put_i32_le(0);
+        let packet = src.clone();
+        let desc = RigidBodyDescCodec::default().decode(&mut src).unwrap();
+        assert!(desc.name.is_empty());
+        assert_eq!(desc.id, 4);
+        assert!(src.is_empty());
+
+        let mut encoded = BytesMut::new();
+        RigidBodyDescCodec::default()
+            .encode(desc, &mut encoded)
+            .unwrap();
+        assert_eq!(encoded, packet);
+    }
+
+    #[test]
+    fn rigid_body_desc_versions() {
+        init();
+        let desc = |id, marker_count, marker_names| {
+            let mut src = BytesMut::new();
+            src.extend_from_slice(b"rb\0");
+            src.put_i32_le(id);
+            src.put_i32_le(-1);
+            for v in [0.1, 0.2, 0.3] {
+                src.put_f32_le(v);
+            }
+            if let Some(count) = marker_count {
+                src.put_i32_le(count);
+                for i in 0..count {
+                    for v in [i as f32, 1.0, 2.0] {
+                        src.put_f32_le(v);
+                    }
+                }
+                for i in 0..count {
+                    src.put_i32_le(i + 10);
+                }
+                if marker_names {
+                    for i in 0..count {
+                        src.extend_from_slice(format!("m{}\0", i).as_bytes());
+                    }
+                }
+            }
+            src
+        };
+        for (version, marker_count) in [((2, 0), None), ((3, 1), Some(2)), ((4, 0), Some(2))] {
+            let version = NatNetVersion::new(version.0, version.1, 0, 0);
+            let marker_names = version >= NatNetVersion::new(4, 0, 0, 0);
+            let mut src = BytesMut::new();
+            src.put_u16_le(0);
+            src.put_u32_le(2);
+            for id in [1, 2] {
+                src.put_u32_le(1);
+                src.extend_from_slice(&desc(id, marker_count, marker_names));
+            }
+            let packet = src.clone();
+            let mut codec = ModelDefCodec::new(version);
+            let model = codec.decode(&mut src).expect("Failed to decode ModelDef");
+            assert!(src.is_empty());
+            let descs: Vec<&RigidBodyDesc> = model.rigid_body_descs().collect();
+            assert_eq!(descs.len(), 2);
+            assert_eq!(descs[1].id, 2);
+            assert_eq!(descs[1].pos, glam::vec3(0.1, 0.2, 0.3));
+            if marker_count.is_some() {
+                assert_eq!(
+                    descs[1].marker_offsets,
+                    vec![glam::vec3(0.0, 1.0, 2.0), glam::vec3(1.0, 1.0, 2.0)]
+                );
+                assert_eq!(descs[1].marker_active_labels, vec![10, 11]);
+            } else {
+                assert_eq!(descs[1].marker_count, 0);
+            }
+            if marker_names {
+                // names are decoded without their null terminators
+                assert_eq!(descs[1].marker_names, vec!["m0", "m1"]);
+            } else {
+                assert!(descs[1].marker_names.is_empty());
+            }
+
+            let mut encoded = BytesMut::new();
+            codec.encode(model, &mut encoded).unwrap();
+            assert_eq!(encoded, packet);
+        }
+    }
+
+    #[test]
+    fn modeldef_lookup() {
+        init();
+        let packet = sample_model_def_packet();
+        let model = match Message::from_bytes(&packet).expect("Failed to decode message") {
+            Message::ModelDef(model) => model,
+            val => panic!("Expected ModelDef, got {:?}", val),
+        };
+        let desc = model
+            .rigid_body_desc(2016)
+            .expect("Missing RigidBodyDesc 2016");
+        assert_eq!(desc.name, "lab1");
+        assert_eq!(desc.marker_count, 16);
+        assert!(model.rigid_body_desc(1234).is_none());
+        let desc = model
+            .marker_set_desc("scanner11")
+            .expect("Missing MarkerSetDesc scanner11");
+        assert_eq!(desc.marker_count, 24);
+        assert_eq!(desc.marker_names[0], "Marker 001");
+    }
+
+    #[test]
+    fn modeldef_interned_names() {
+        init();
+        let packet = sample_model_def_packet();
+        let mut interner = StringInterner::new();
+        let mut codec = ModelDefCodec::default();
+        let first = codec
+            .decode_interned(&mut BytesMut::from(&packet[2..]), &mut interner)
+            .unwrap();
+        let symbols = interner.len();
+        let second = codec
+            .decode_interned(&mut BytesMut::from(&packet[2..]), &mut interner)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(interner.len(), symbols);
+
+        let (first_name, first_markers) = &first.marker_sets[0];
+        let (second_name, second_markers) = &second.marker_sets[0];
+        assert!(Arc::ptr_eq(first_name, second_name));
+        assert!(Arc::ptr_eq(&first_markers[0], &second_markers[0]));
+        assert!(Arc::ptr_eq(
+            &first.rigid_bodies[0].1,
+            &second.rigid_bodies[0].1
+        ));
+        let (_, lab1) = first
+            .rigid_bodies
+            .iter()
+            .find(|(id, _)| *id == 2016)
+            .unwrap();
+        assert_eq!(&**lab1, "lab1");
+
+        // the same names as a full decode
+        let model = codec
+            .decode(&mut BytesMut::from(&packet[2..]))
+            .unwrap()
+            .intern_names(&mut interner);
+        assert_eq!(model, first);
+        assert_eq!(interner.len(), symbols);
+    }
+
+    #[test]
+    fn frame_markerset_names_interned() {
+        init();
+        let frame = Message::from_bytes(SAMPLE_FRAME_DATA)
+            .unwrap()
+            .into_frame_data()
+            .unwrap();
+        let codec = FrameDataCodec::default();
+        let mut interner = StringInterner::new();
+        let first = codec
+            .markerset_names_interned(&SAMPLE_FRAME_DATA[2..], &mut interner)
+            .unwrap();
+        let names: Vec<&str> = first.iter().map(|name| &**name).collect();
+        let expected: Vec<&str> = frame.markersets.iter().map(|ms| ms.name.as_str()).collect();
+        assert_eq!(names, expected);
+
+        let symbols = interner.len();
+        let second = codec
+            .markerset_names_interned(&SAMPLE_FRAME_DATA[2..], &mut interner)
+            .unwrap();
+        assert_eq!(interner.len(), symbols);
+        assert!(first
+            .iter()
+            .zip(&second)
+            .all(|(first, second)| Arc::ptr_eq(first, second)));
+    }
+
+    #[test]
+    fn modeldef_diff() {
+        init();
+        let packet = sample_model_def_packet();
+        let model = match Message::from_bytes(&packet).expect("Failed to decode message") {
+            Message::ModelDef(model) => model,
+            val => panic!("Expected ModelDef, got {:?}", val),
+        };
+        assert!(model.diff(&model).is_empty());
+
+        let mut changed = model.clone();
+        changed.dataset.retain(
+            |data| !matches!(data, ModelDefData::RigidBodyDesc { data, .. } if data.id == 2016),
+        );
+        let diff = model.diff(&changed);
+        assert_eq!(diff.removed_rigid_bodies, vec![2016]);
+        assert_eq!(
+            diff,
+            ModelDefDiff {
+                removed_rigid_bodies: vec![2016],
+                ..Default::default()
+            }
+        );
+        assert_eq!(changed.diff(&model).added_rigid_bodies, vec![2016]);
+
+        for data in changed.dataset.iter_mut() {
+            if let ModelDefData::MarkerSetDesc { data, .. } = data {
+                if data.name == "scanner11" {
+                    data.name = "scanner12".to_string();
+                }
+            }
+        }
+        assert_eq!(
+            model.diff(&changed).renamed_marker_sets,
+            vec![("scanner11".to_string(), "scanner12".to_string())]
+        );
+    }
+
+    #[test]
+    fn stamps_mid_exposure_time() {
+        let stamps = Stamps {
+            timestamp: 1.0,
+            timestamp_mid: 25_000_000,
+            ..Default::default()
+        };
+        assert_eq!(stamps.mid_exposure_time(1.0), 25_000_000.0);
+        assert_eq!(stamps.mid_exposure_time(10_000_000.0), 2.5);
+    }
+
+    #[test]
+    fn approx_comparisons() {
+        use approx::{quat_approx_eq, vec3_approx_eq};
+
+        let q = Quat::from_rotation_y(0.7);
+        assert!(quat_approx_eq(q, -q, 1e-6));
+        assert!(quat_approx_eq(q, q.normalize(), 1e-6));
+        assert!(!quat_approx_eq(q, Quat::IDENTITY, 1e-6));
+        let v = glam::vec3(1.0, 2.0, 3.0);
+        assert!(vec3_approx_eq(v, v + Vec3::splat(1e-7), 1e-6));
+        assert!(!vec3_approx_eq(v, -v, 1e-6));
+    }
+
+    #[test]
+    fn reorder_buffer() {
+        let frame = |frame_number| FrameData {
+            frame_number,
+            ..Default::default()
+        };
+        let mut reorder = ReorderBuffer::new(2);
+        for frame_number in [1, 3, 2] {
+            reorder.push(frame(frame_number));
+        }
+        let released: Vec<u32> = std::iter::from_fn(|| reorder.pop())
+            .map(|f| f.frame_number)
+            .collect();
+        assert_eq!(released, vec![1, 2, 3]);
+
+        // too late, then a gap that is given up on once the window is exceeded
+        reorder.push(frame(2));
+        assert_eq!(reorder.dropped(), 1);
+        reorder.push(frame(5));
+        assert!(reorder.pop().is_none());
+        reorder.push(frame(7));
+        assert_eq!(reorder.pop().map(|f| f.frame_number), Some(5));
+        assert!(reorder.pop().is_none());
+        reorder.flush();
+        assert_eq!(reorder.pop().map(|f| f.frame_number), Some(7));
+
+        let mut reorder = ReorderBuffer::new(2);
+        for frame_number in [u32::MAX - 1, 0, u32::MAX] {
+            reorder.push(frame(frame_number));
+        }
+        let released: Vec<u32> = std::iter::from_fn(|| reorder.pop())
+            .map(|f| f.frame_number)
+            .collect();
+        assert_eq!(released, vec![u32::MAX - 1, u32::MAX, 0]);
+
+        // frame numbers restarting far behind release the held frames and start over
+        let mut reorder = ReorderBuffer::new(2);
+        for frame_number in [100, 101, 103, 1, 3, 2] {
+            reorder.push(frame(frame_number));
+        }
+        let released: Vec<u32> = std::iter::from_fn(|| reorder.pop())
+            .map(|f| f.frame_number)
+            .collect();
+        assert_eq!(released, vec![100, 101, 103, 1, 2, 3]);
+        assert_eq!(reorder.dropped(), 0);
+    }
+
+    #[test]
+    fn rigid_body_asset_flags() {
+        let asset = RigidBodyAsset {
+            id: 1,
+            pos: Vec3::ZERO,
+            rot: Quat::IDENTITY,
+            marker_error: 0.0,
+            param: 0x05,
+        };
+        let flags = asset.flags();
+        assert!(flags.tracking_valid());
+        assert_eq!(flags.param, 0x05);
+        assert!(!AssetFlags::from_param(0x04).tracking_valid());
+    }
+
+    #[test]
+    fn align_frames_recovers_transform() {
+        let mocap = [
+            glam::vec3(0.0, 0.0, 0.0),
+            glam::vec3(1.0, 0.0, 0.0),
+            glam::vec3(0.0, 2.0, 0.0),
+            glam::vec3(0.5, 0.5, 1.5),
+        ];
+        let rot = Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 2.0);
+        let translation = glam::vec3(0.4, -2.0, 1.0);
+        let robot: Vec<Vec3> = mocap
+            .iter()
+            .map(|p| 1.5 * (rot * *p) + translation)
+            .collect();
+
+        let (t, r, scale) = align_frames(&mocap, &robot).expect("Failed to align");
+        assert!(approx::vec3_approx_eq(t, translation, 1e-4));
+        assert!(approx::quat_approx_eq(r, rot, 1e-5));
+        assert!((scale - 1.5).abs() < 1e-5);
+
+        assert!(align_frames(&mocap[..2], &robot[..2]).is_none());
+        assert!(align_frames(&mocap, &robot[..3]).is_none());
+    }
+
+    #[test]
+    fn rigid_body_interpolate() {
+        let start = rigid_body(4, glam::vec3(0.0, 1.0, 0.0), Quat::IDENTITY);
+        let end = rigid_body(
+            4,
+            glam::vec3(2.0, 1.0, -4.0),
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+        );
+        let mid = start.interpolate(&end, 0.5);
+        assert_eq!(mid.id, 4);
+        let midpoint = glam::vec3(1.0, 1.0, -2.0);
+        assert!(approx::vec3_approx_eq(mid.pos, midpoint, 1e-6));
+        assert!(approx::quat_approx_eq(
+            mid.rot,
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_4),
+            1e-6
+        ));
+        assert_eq!(start.interpolate(&end, 0.0).pos, start.pos);
+        assert_eq!(start.interpolate(&end, 1.0).pos, end.pos);
+    }
+
+    #[test]
+    fn payloadless_messages() {
+        init();
+        let message = Message::from_bytes(&[0x0a, 0x00]).expect("Failed to decode KeepAlive");
+        assert!(matches!(message, Message::KeepAlive));
+        let message = Message::from_bytes(&keepalive_packet()).unwrap();
+        assert!(matches!(message, Message::KeepAlive));
+        let message = Message::from_bytes(&[0x09, 0x00]).unwrap();
+        assert!(matches!(message, Message::Disconnect));
+        let message = Message::from_bytes(&[0x0b, 0x00]).unwrap();
+        assert!(matches!(message, Message::DisconnectByTimeout));
+        match Message::from_bytes(&[0x00, 0x00]).unwrap() {
+            Message::Ping(sender) => assert!(sender.app_name.is_empty()),
+            val => panic!("Expected Ping, got {:?}", val),
+        }
+
+        // the empty size is written and consumed, leaving the next message in place
+        let mut src = Message::KeepAlive.to_bytes().unwrap();
+        src.extend_from_slice(&Message::Disconnect.to_bytes().unwrap());
+        assert!(matches!(Message::decode(&mut src), Ok(Message::KeepAlive)));
+        assert!(matches!(Message::decode(&mut src), Ok(Message::Disconnect)));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn tum_trajectory() {
+        let frame = |timestamp, bodies| FrameData {
+            stamps: Stamps {
+                timestamp,
+                ..Default::default()
+            },
+            rigid_bodies: bodies,
+            ..Default::default()
+        };
+        let frames = vec![
+            frame(
+                1.5,
+                vec![
+                    rigid_body(2, Vec3::ONE, Quat::IDENTITY),
+                    rigid_body(1, glam::vec3(0.5, -1.0, 2.0), Quat::IDENTITY),
+                ],
+            ),
+            frame(1.25, vec![]),
+            frame(
+                1.75,
+                vec![rigid_body(
+                    1,
+                    glam::vec3(0.25, 0.0, 0.0),
+                    Quat::from_xyzw(0.0, 1.0, 0.0, 0.0),
+                )],
+            ),
+        ];
+        let mut out = Vec::new();
+        assert_eq!(write_tum_trajectory(&mut out, 1, &frames).unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1.500000 0.5 -1 2 0 0 0 1\n1.750000 0.25 0 0 0 1 0 0\n"
+        );
+    }
 }
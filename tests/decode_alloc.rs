@@ -0,0 +1,146 @@
+use bytes::BytesMut;
+use glam::{Quat, Vec3};
+use optitrack::{
+    Asset, Device, DeviceChannel, ForcePlate, ForcePlateChannel, FrameData, FrameDataCodec,
+    MarkerAsset, RigidBody, RigidBodyAsset, Skeleton,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const SAMPLE_FRAME_DATA: &[u8] = include_bytes!("../src/FrameData.bin");
+
+// Counts the allocations made by the thread that enabled counting, so the test harness's own
+// threads don't show up
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+fn rigid_body(id: u32) -> RigidBody {
+    RigidBody {
+        id,
+        pos: Vec3::new(1.0, 2.0, 3.0),
+        rot: Quat::IDENTITY,
+        is_tracking_valid: true,
+        mean_marker_err: 0.001,
+    }
+}
+
+// A frame using every section with nested vectors
+fn nested_frame_packet() -> BytesMut {
+    let frame = FrameData {
+        skeleton_count: 1,
+        skeletons: vec![Skeleton {
+            id: 1,
+            rigid_body_count: 2,
+            rigid_bodies: vec![rigid_body(1), rigid_body(2)],
+        }],
+        asset_count: 1,
+        assets: vec![Asset {
+            id: 2,
+            rigid_body_count: 1,
+            rigid_bodies: vec![RigidBodyAsset {
+                id: 3,
+                pos: Vec3::ZERO,
+                rot: Quat::IDENTITY,
+                marker_error: 0.0,
+                param: 1,
+            }],
+            markers: MarkerAsset {
+                marker_count: 2,
+                marker_ids: vec![4, 5],
+                marker_positions: vec![Vec3::X, Vec3::Y],
+                marker_sizes: vec![0.01, 0.01],
+                marker_params: vec![0, 0],
+                marker_residuals: vec![0.0, 0.0],
+            },
+        }],
+        force_plate_count: 1,
+        force_plates: vec![ForcePlate {
+            id: 6,
+            channel_count: 2,
+            channels: vec![
+                ForcePlateChannel {
+                    value_count: 3,
+                    values: vec![1, 2, 3],
+                },
+                ForcePlateChannel {
+                    value_count: 1,
+                    values: vec![4],
+                },
+            ],
+        }],
+        device_count: 1,
+        devices: vec![Device {
+            id: 7,
+            device_type: None,
+            channel_count: 1,
+            channels: vec![DeviceChannel {
+                value_count: 2,
+                values: vec![5, 6],
+            }],
+        }],
+        ..Default::default()
+    };
+    frame.to_packet().unwrap()
+}
+
+#[test]
+fn decode_into_steady_state_does_not_allocate() {
+    let nested = nested_frame_packet();
+    for packet in [SAMPLE_FRAME_DATA, &nested[..]] {
+        let mut codec = FrameDataCodec::default();
+        let mut frame = FrameData::default();
+        // the source buffers are copied up front so only decoding is counted
+        let mut sources: Vec<BytesMut> = (0..4).map(|_| BytesMut::from(&packet[2..])).collect();
+        codec
+            .decode_into(&mut sources.pop().unwrap(), &mut frame)
+            .unwrap();
+        let expected = frame.clone();
+
+        let allocations = count_allocations(|| {
+            for src in sources.iter_mut() {
+                codec.decode_into(src, &mut frame).unwrap();
+            }
+        });
+        assert_eq!(allocations, 0);
+        assert_eq!(frame, expected);
+    }
+}